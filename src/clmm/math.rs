@@ -0,0 +1,405 @@
+use crate::clmm::types::{Liquidity, SqrtPriceX96, SwapStep, Tick};
+use crate::support::math::mul_div;
+use crate::support::rational::Rounding;
+use crate::types::Balance;
+use crate::{
+    ensure, to_balance, MathError,
+    MathError::{Overflow, ZeroReserve},
+};
+use core::convert::TryFrom;
+use num_traits::Zero;
+use primitive_types::U256;
+use sp_arithmetic::Permill;
+
+/// Number of fractional bits of `SqrtPriceX96`.
+pub const RESOLUTION: usize = 96;
+
+/// Minimum tick for which the sqrt price can be calculated, equal to `log_1.0001(2^-128)`.
+pub const MIN_TICK: Tick = -887272;
+/// Maximum tick for which the sqrt price can be calculated, equal to `log_1.0001(2^128)`.
+pub const MAX_TICK: Tick = 887272;
+
+/// Sqrt price at `MIN_TICK`.
+pub const MIN_SQRT_PRICE: SqrtPriceX96 = U256([4295128739, 0, 0, 0]);
+/// Sqrt price at `MAX_TICK`.
+pub const MAX_SQRT_PRICE: SqrtPriceX96 = U256([0x5d951d5263988d26, 0xefd1fc6a50648849, 0xfffd8963, 0]);
+
+/// `sqrt(1.0001)^-(2^i)` as Q128.128 for bits `i` of the absolute tick value.
+const TICK_RATIOS: [u128; 20] = [
+    0xfffcb933bd6fad37aa2d162d1a594001,
+    0xfff97272373d413259a46990580e213a,
+    0xfff2e50f5f656932ef12357cf3c7fdcc,
+    0xffe5caca7e10e4e61c3624eaa0941cd0,
+    0xffcb9843d60f6159c9db58835c926644,
+    0xff973b41fa98c081472e6896dfb254c0,
+    0xff2ea16466c96a3843ec78b326b52861,
+    0xfe5dee046a99a2a811c461f1969c3053,
+    0xfcbe86c7900a88aedcffc83b479aa3a4,
+    0xf987a7253ac413176f2b074cf7815e54,
+    0xf3392b0822b70005940c7a398e4b70f3,
+    0xe7159475a2c29b7443b29c7fa6e889d9,
+    0xd097f3bdfd2022b8845ad8f792aa5825,
+    0xa9f746462d870fdf8a65dc1f90e061e5,
+    0x70d869a156d2a1b890bb3df62baf32f7,
+    0x31be135f97d08fd981231505542fcfa6,
+    0x9aa508b5b7a84e1c677de54f3e99bc9,
+    0x5d6af8dedb81196699c329225ee604,
+    0x2216e584f5fa1ea926041bedfe98,
+    0x48a170391f7dc42444e8fa2,
+];
+
+#[inline]
+fn q96() -> U256 {
+    U256::one() << RESOLUTION
+}
+
+/// Calculating sqrt price at given tick.
+/// Formula : SQRT_PRICE = sqrt(1.0001^TICK) * 2^96
+///
+/// The result is rounded up.
+///
+/// - `tick` - tick in range `MIN_TICK..=MAX_TICK`
+///
+/// Returns MathError in case of error
+pub fn calculate_sqrt_price_at_tick(tick: Tick) -> Result<SqrtPriceX96, MathError> {
+    let abs_tick = tick.unsigned_abs();
+    ensure!(abs_tick <= MAX_TICK.unsigned_abs(), Overflow);
+
+    let mut ratio = if abs_tick & 1 != 0 {
+        U256::from(TICK_RATIOS[0])
+    } else {
+        U256::one() << 128
+    };
+
+    for (i, tick_ratio) in TICK_RATIOS.iter().enumerate().skip(1) {
+        if abs_tick & (1 << i) != 0 {
+            ratio = ratio.checked_mul(U256::from(*tick_ratio)).ok_or(Overflow)? >> 128;
+        }
+    }
+
+    if tick > 0 {
+        ratio = U256::MAX.checked_div(ratio).ok_or(Overflow)?;
+    }
+
+    // Q128.128 to Q64.96, rounding up so that the result of `calculate_tick_at_sqrt_price` is consistent
+    let remainder = ratio & U256::from(u32::MAX);
+    let sqrt_price = (ratio >> 32)
+        .checked_add(U256::from(!remainder.is_zero() as u8))
+        .ok_or(Overflow)?;
+
+    Ok(sqrt_price)
+}
+
+/// Calculating the greatest tick whose sqrt price is less than or equal to the given sqrt price.
+///
+/// - `sqrt_price` - sqrt price in range `MIN_SQRT_PRICE..MAX_SQRT_PRICE`
+///
+/// Returns MathError in case of error
+pub fn calculate_tick_at_sqrt_price(sqrt_price: SqrtPriceX96) -> Result<Tick, MathError> {
+    ensure!(sqrt_price >= MIN_SQRT_PRICE && sqrt_price < MAX_SQRT_PRICE, Overflow);
+
+    let (mut low, mut high) = (MIN_TICK, MAX_TICK);
+
+    while low < high {
+        // Bare math is safe because ticks are bound by `MAX_TICK`
+        let mid = low + (high - low + 1) / 2;
+        if calculate_sqrt_price_at_tick(mid)? <= sqrt_price {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+
+    Ok(low)
+}
+
+/// Calculating amount of asset 0 between two sqrt prices for given liquidity.
+/// Formula : AMOUNT_0 = LIQUIDITY * 2^96 * (SQRT_PRICE_UPPER - SQRT_PRICE_LOWER) / (SQRT_PRICE_UPPER * SQRT_PRICE_LOWER)
+///
+/// - `sqrt_price_a` - sqrt price at one end of the range
+/// - `sqrt_price_b` - sqrt price at the other end of the range
+/// - `liquidity` - liquidity in the range
+/// - `rounding` - direction of rounding
+///
+/// Returns MathError in case of error
+pub fn calculate_amount0_delta(
+    sqrt_price_a: SqrtPriceX96,
+    sqrt_price_b: SqrtPriceX96,
+    liquidity: Liquidity,
+    rounding: Rounding,
+) -> Result<Balance, MathError> {
+    let amount = amount0_delta(sqrt_price_a, sqrt_price_b, liquidity, rounding)?;
+    to_balance!(amount)
+}
+
+/// Calculating amount of asset 1 between two sqrt prices for given liquidity.
+/// Formula : AMOUNT_1 = LIQUIDITY * (SQRT_PRICE_UPPER - SQRT_PRICE_LOWER) / 2^96
+///
+/// - `sqrt_price_a` - sqrt price at one end of the range
+/// - `sqrt_price_b` - sqrt price at the other end of the range
+/// - `liquidity` - liquidity in the range
+/// - `rounding` - direction of rounding
+///
+/// Returns MathError in case of error
+pub fn calculate_amount1_delta(
+    sqrt_price_a: SqrtPriceX96,
+    sqrt_price_b: SqrtPriceX96,
+    liquidity: Liquidity,
+    rounding: Rounding,
+) -> Result<Balance, MathError> {
+    let amount = amount1_delta(sqrt_price_a, sqrt_price_b, liquidity, rounding)?;
+    to_balance!(amount)
+}
+
+/// Calculating sqrt price after given amount is sent to the pool.
+/// The result is rounded so that the price moves less, i.e. in favour of the pool.
+///
+/// - `sqrt_price` - current sqrt price
+/// - `liquidity` - liquidity in the range
+/// - `amount_in` - amount sent to the pool
+/// - `zero_for_one` - true if asset 0 is sent to the pool, false for asset 1
+///
+/// Returns MathError in case of error
+pub fn calculate_next_sqrt_price_from_input(
+    sqrt_price: SqrtPriceX96,
+    liquidity: Liquidity,
+    amount_in: Balance,
+    zero_for_one: bool,
+) -> Result<SqrtPriceX96, MathError> {
+    ensure!(!sqrt_price.is_zero(), ZeroReserve);
+    ensure!(liquidity != 0, ZeroReserve);
+
+    if zero_for_one {
+        next_sqrt_price_from_amount0_rounding_up(sqrt_price, liquidity, amount_in, true)
+    } else {
+        next_sqrt_price_from_amount1_rounding_down(sqrt_price, liquidity, amount_in, true)
+    }
+}
+
+/// Calculating sqrt price after given amount is received from the pool.
+/// The result is rounded so that the price moves more, i.e. in favour of the pool.
+///
+/// - `sqrt_price` - current sqrt price
+/// - `liquidity` - liquidity in the range
+/// - `amount_out` - amount received from the pool
+/// - `zero_for_one` - true if asset 1 is received from the pool, false for asset 0
+///
+/// Returns MathError in case of error
+pub fn calculate_next_sqrt_price_from_output(
+    sqrt_price: SqrtPriceX96,
+    liquidity: Liquidity,
+    amount_out: Balance,
+    zero_for_one: bool,
+) -> Result<SqrtPriceX96, MathError> {
+    ensure!(!sqrt_price.is_zero(), ZeroReserve);
+    ensure!(liquidity != 0, ZeroReserve);
+
+    if zero_for_one {
+        next_sqrt_price_from_amount1_rounding_down(sqrt_price, liquidity, amount_out, false)
+    } else {
+        next_sqrt_price_from_amount0_rounding_up(sqrt_price, liquidity, amount_out, false)
+    }
+}
+
+/// Calculating a swap within a single liquidity range, moving the price from current sqrt price towards target sqrt price.
+/// Direction of the swap is given by the target price: asset 0 is sold if the target price is lower than the current price.
+///
+/// - `sqrt_price_current` - current sqrt price
+/// - `sqrt_price_target` - sqrt price which cannot be exceeded, i.e. next initialized tick or price limit
+/// - `liquidity` - liquidity in the range
+/// - `amount_remaining` - amount remaining to be swapped
+/// - `exact_in` - true if `amount_remaining` is amount in including fee, false if it is amount out
+/// - `fee` - trade fee taken from amount in
+///
+/// Returns MathError in case of error
+pub fn calculate_swap_step(
+    sqrt_price_current: SqrtPriceX96,
+    sqrt_price_target: SqrtPriceX96,
+    liquidity: Liquidity,
+    amount_remaining: Balance,
+    exact_in: bool,
+    fee: Permill,
+) -> Result<SwapStep, MathError> {
+    let zero_for_one = sqrt_price_current >= sqrt_price_target;
+    let fee_parts = U256::from(fee.deconstruct());
+    let fee_accuracy = U256::from(Permill::one().deconstruct());
+    let fee_complement = fee_accuracy.checked_sub(fee_parts).ok_or(Overflow)?;
+    let amount_remaining_hp = U256::from(amount_remaining);
+
+    // Amount in or out required to reach the target price
+    let amount_to_target = match (exact_in, zero_for_one) {
+        (true, true) => amount0_delta(sqrt_price_target, sqrt_price_current, liquidity, Rounding::Up)?,
+        (true, false) => amount1_delta(sqrt_price_current, sqrt_price_target, liquidity, Rounding::Up)?,
+        (false, true) => amount1_delta(sqrt_price_target, sqrt_price_current, liquidity, Rounding::Down)?,
+        (false, false) => amount0_delta(sqrt_price_current, sqrt_price_target, liquidity, Rounding::Down)?,
+    };
+
+    let sqrt_price_next = if exact_in {
+        let amount_remaining_less_fee = to_balance!(mul_div(
+            amount_remaining_hp,
+            fee_complement,
+            fee_accuracy,
+            Rounding::Down
+        )?)?;
+        if U256::from(amount_remaining_less_fee) >= amount_to_target {
+            sqrt_price_target
+        } else {
+            calculate_next_sqrt_price_from_input(
+                sqrt_price_current,
+                liquidity,
+                amount_remaining_less_fee,
+                zero_for_one,
+            )?
+        }
+    } else if amount_remaining_hp >= amount_to_target {
+        sqrt_price_target
+    } else {
+        calculate_next_sqrt_price_from_output(sqrt_price_current, liquidity, amount_remaining, zero_for_one)?
+    };
+
+    let target_reached = sqrt_price_next == sqrt_price_target;
+
+    let (amount_in, amount_out) = if zero_for_one {
+        (
+            if target_reached && exact_in {
+                amount_to_target
+            } else {
+                amount0_delta(sqrt_price_next, sqrt_price_current, liquidity, Rounding::Up)?
+            },
+            if target_reached && !exact_in {
+                amount_to_target
+            } else {
+                amount1_delta(sqrt_price_next, sqrt_price_current, liquidity, Rounding::Down)?
+            },
+        )
+    } else {
+        (
+            if target_reached && exact_in {
+                amount_to_target
+            } else {
+                amount1_delta(sqrt_price_current, sqrt_price_next, liquidity, Rounding::Up)?
+            },
+            if target_reached && !exact_in {
+                amount_to_target
+            } else {
+                amount0_delta(sqrt_price_current, sqrt_price_next, liquidity, Rounding::Down)?
+            },
+        )
+    };
+
+    let amount_in = to_balance!(amount_in)?;
+    // Rounding of the price can result in slightly more than requested
+    let amount_out = if exact_in {
+        to_balance!(amount_out)?
+    } else {
+        to_balance!(amount_out.min(amount_remaining_hp))?
+    };
+
+    let fee_amount = if exact_in && !target_reached {
+        // Whatever is left of the remaining amount is taken as fee
+        amount_remaining.checked_sub(amount_in).ok_or(Overflow)?
+    } else {
+        to_balance!(mul_div(U256::from(amount_in), fee_parts, fee_complement, Rounding::Up)?)?
+    };
+
+    Ok(SwapStep {
+        sqrt_price_next,
+        amount_in,
+        amount_out,
+        fee_amount,
+    })
+}
+
+fn amount0_delta(
+    sqrt_price_a: SqrtPriceX96,
+    sqrt_price_b: SqrtPriceX96,
+    liquidity: Liquidity,
+    rounding: Rounding,
+) -> Result<U256, MathError> {
+    let (lower, upper) = if sqrt_price_a <= sqrt_price_b {
+        (sqrt_price_a, sqrt_price_b)
+    } else {
+        (sqrt_price_b, sqrt_price_a)
+    };
+    ensure!(!lower.is_zero(), ZeroReserve);
+
+    let numerator = U256::from(liquidity) << RESOLUTION;
+    // Bare math is safe because of the ordering above
+    let price_diff = upper - lower;
+
+    let amount = mul_div(numerator, price_diff, upper, rounding)?;
+    mul_div(amount, U256::one(), lower, rounding)
+}
+
+fn amount1_delta(
+    sqrt_price_a: SqrtPriceX96,
+    sqrt_price_b: SqrtPriceX96,
+    liquidity: Liquidity,
+    rounding: Rounding,
+) -> Result<U256, MathError> {
+    let (lower, upper) = if sqrt_price_a <= sqrt_price_b {
+        (sqrt_price_a, sqrt_price_b)
+    } else {
+        (sqrt_price_b, sqrt_price_a)
+    };
+
+    // Bare math is safe because of the ordering above
+    mul_div(U256::from(liquidity), upper - lower, q96(), rounding)
+}
+
+/// Formula : SQRT_PRICE_NEXT = LIQUIDITY * SQRT_PRICE / (LIQUIDITY +- AMOUNT * SQRT_PRICE)
+/// Always rounds up, so that the price moves less when asset 0 is added and more when asset 0 is removed.
+fn next_sqrt_price_from_amount0_rounding_up(
+    sqrt_price: SqrtPriceX96,
+    liquidity: Liquidity,
+    amount: Balance,
+    add: bool,
+) -> Result<SqrtPriceX96, MathError> {
+    if amount.is_zero() {
+        return Ok(sqrt_price);
+    }
+
+    let numerator = U256::from(liquidity) << RESOLUTION;
+    let product = U256::from(amount).checked_mul(sqrt_price);
+
+    if add {
+        match product.and_then(|p| numerator.checked_add(p)) {
+            Some(denominator) => mul_div(numerator, sqrt_price, denominator, Rounding::Up),
+            // Alternative form which does not overflow but loses some precision
+            None => mul_div(
+                numerator,
+                U256::one(),
+                (numerator / sqrt_price)
+                    .checked_add(U256::from(amount))
+                    .ok_or(Overflow)?,
+                Rounding::Up,
+            ),
+        }
+    } else {
+        let denominator = numerator.checked_sub(product.ok_or(Overflow)?).ok_or(Overflow)?;
+        ensure!(!denominator.is_zero(), ZeroReserve);
+        mul_div(numerator, sqrt_price, denominator, Rounding::Up)
+    }
+}
+
+/// Formula : SQRT_PRICE_NEXT = SQRT_PRICE +- AMOUNT * 2^96 / LIQUIDITY
+/// Always rounds down, so that the price moves less when asset 1 is added and more when asset 1 is removed.
+fn next_sqrt_price_from_amount1_rounding_down(
+    sqrt_price: SqrtPriceX96,
+    liquidity: Liquidity,
+    amount: Balance,
+    add: bool,
+) -> Result<SqrtPriceX96, MathError> {
+    let numerator = U256::from(amount) << RESOLUTION;
+    let liquidity = U256::from(liquidity);
+
+    if add {
+        let quotient = mul_div(numerator, U256::one(), liquidity, Rounding::Down)?;
+        sqrt_price.checked_add(quotient).ok_or(Overflow)
+    } else {
+        let quotient = mul_div(numerator, U256::one(), liquidity, Rounding::Up)?;
+        ensure!(sqrt_price > quotient, ZeroReserve);
+        // Bare math is safe because of the check above
+        Ok(sqrt_price - quotient)
+    }
+}
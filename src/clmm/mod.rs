@@ -0,0 +1,7 @@
+mod math;
+pub mod types;
+
+#[cfg(test)]
+mod tests;
+
+pub use math::*;
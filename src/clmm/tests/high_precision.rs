@@ -0,0 +1,154 @@
+use super::ONE;
+use crate::clmm::types::{Liquidity, Tick};
+use crate::clmm::*;
+use crate::support::rational::Rounding;
+use crate::test_utils::prop_assert_rational_approx_eq;
+use crate::types::Balance;
+
+use primitive_types::U256;
+use proptest::prelude::*;
+use rug::ops::Pow;
+use rug::{Float, Integer, Rational};
+
+const PRECISION: u32 = 256;
+
+/// Convert a `U256` to an arbitrary precision integer.
+fn to_integer(x: U256) -> Integer {
+    Integer::from_str_radix(&x.to_string(), 10).unwrap()
+}
+
+/// Calculate `sqrt(1.0001^tick) * 2^96` with 256 bits of precision.
+fn precise_sqrt_price_at_tick(tick: Tick) -> Rational {
+    let base = Float::with_val(PRECISION, Rational::from((10001, 10000)));
+    let sqrt_price = base.pow(tick).sqrt() * Float::with_val(PRECISION, Integer::from(1) << 96u32);
+    sqrt_price.to_rational().unwrap()
+}
+
+/// Calculate the amount of asset 0 between two sqrt prices with arbitrary precision.
+fn precise_amount0_delta(lower: U256, upper: U256, liquidity: Liquidity) -> Rational {
+    let (lower, upper) = (to_integer(lower), to_integer(upper));
+    Rational::from((Integer::from(liquidity) << 96u32) * (upper.clone() - lower.clone())) / (upper * lower)
+}
+
+/// Calculate the amount of asset 1 between two sqrt prices with arbitrary precision.
+fn precise_amount1_delta(lower: U256, upper: U256, liquidity: Liquidity) -> Rational {
+    let (lower, upper) = (to_integer(lower), to_integer(upper));
+    Rational::from((Integer::from(liquidity) * (upper - lower), Integer::from(1) << 96u32))
+}
+
+fn tick() -> impl Strategy<Value = Tick> {
+    MIN_TICK..=MAX_TICK
+}
+
+fn tick_range() -> impl Strategy<Value = (Tick, Tick)> {
+    (-100_000..100_000, 1..50_000).prop_map(|(lower, width)| (lower, lower + width))
+}
+
+fn liquidity() -> impl Strategy<Value = Liquidity> {
+    ONE..1_000_000_000 * ONE
+}
+
+fn trade_amount() -> impl Strategy<Value = Balance> {
+    1000..10_000 * ONE
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(1_000))]
+    #[test]
+    fn sqrt_price_at_tick_is_accurate(tick in tick()) {
+        let sqrt_price = calculate_sqrt_price_at_tick(tick).unwrap();
+        let expected = precise_sqrt_price_at_tick(tick);
+
+        // allowed error is one unit of rounding plus a relative error of 1e-18
+        let tolerance = Rational::from(1) + expected.clone() / Rational::from(10u128.pow(18));
+        prop_assert_rational_approx_eq!(
+            Rational::from(to_integer(sqrt_price)),
+            expected,
+            tolerance,
+            "sqrt price at tick is not accurate"
+        );
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(1_000))]
+    #[test]
+    fn tick_at_sqrt_price_is_inverse(tick in MIN_TICK..MAX_TICK) {
+        let sqrt_price = calculate_sqrt_price_at_tick(tick).unwrap();
+        let next_sqrt_price = calculate_sqrt_price_at_tick(tick + 1).unwrap();
+
+        prop_assert_eq!(calculate_tick_at_sqrt_price(sqrt_price), Ok(tick));
+        prop_assert_eq!(calculate_tick_at_sqrt_price(next_sqrt_price - 1), Ok(tick));
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(1_000))]
+    #[test]
+    fn amount_deltas_are_rounded_correctly(
+        (lower_tick, upper_tick) in tick_range(),
+        liquidity in liquidity(),
+    ) {
+        let lower = calculate_sqrt_price_at_tick(lower_tick).unwrap();
+        let upper = calculate_sqrt_price_at_tick(upper_tick).unwrap();
+
+        let expected = precise_amount0_delta(lower, upper, liquidity);
+        let up = Rational::from(calculate_amount0_delta(lower, upper, liquidity, Rounding::Up).unwrap());
+        let down = Rational::from(calculate_amount0_delta(lower, upper, liquidity, Rounding::Down).unwrap());
+        prop_assert!(down <= expected && expected <= up);
+        prop_assert!(up - down <= 1);
+
+        let expected = precise_amount1_delta(lower, upper, liquidity);
+        let up = Rational::from(calculate_amount1_delta(lower, upper, liquidity, Rounding::Up).unwrap());
+        let down = Rational::from(calculate_amount1_delta(lower, upper, liquidity, Rounding::Down).unwrap());
+        prop_assert!(down <= expected && expected <= up);
+        prop_assert!(up - down <= 1);
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(1_000))]
+    #[test]
+    fn swap_step_favours_pool(
+        (lower_tick, upper_tick) in tick_range(),
+        liquidity in liquidity(),
+        amount in trade_amount(),
+        zero_for_one in any::<bool>(),
+        exact_in in any::<bool>(),
+        fee in 0..10_000u32,
+    ) {
+        let lower = calculate_sqrt_price_at_tick(lower_tick).unwrap();
+        let upper = calculate_sqrt_price_at_tick(upper_tick).unwrap();
+        let (current, target) = if zero_for_one { (upper, lower) } else { (lower, upper) };
+
+        let step = calculate_swap_step(current, target, liquidity, amount, exact_in, sp_arithmetic::Permill::from_parts(fee)).unwrap();
+
+        if exact_in {
+            prop_assert!(step.amount_in + step.fee_amount <= amount);
+        } else {
+            prop_assert!(step.amount_out <= amount);
+        }
+
+        // the price never moves past the target
+        if zero_for_one {
+            prop_assert!(step.sqrt_price_next >= target && step.sqrt_price_next <= current);
+        } else {
+            prop_assert!(step.sqrt_price_next <= target && step.sqrt_price_next >= current);
+        }
+
+        // amounts exchanged between the prices must not be in favour of the trader
+        let (expected_in, expected_out) = if zero_for_one {
+            (
+                precise_amount0_delta(step.sqrt_price_next, current, liquidity),
+                precise_amount1_delta(step.sqrt_price_next, current, liquidity),
+            )
+        } else {
+            (
+                precise_amount1_delta(current, step.sqrt_price_next, liquidity),
+                precise_amount0_delta(current, step.sqrt_price_next, liquidity),
+            )
+        };
+        prop_assert!(Rational::from(step.amount_in) >= expected_in);
+        prop_assert!(Rational::from(step.amount_out) <= expected_out);
+    }
+}
@@ -0,0 +1,222 @@
+mod high_precision;
+
+use crate::clmm::types::SwapStep;
+use crate::clmm::*;
+use crate::support::rational::Rounding;
+use crate::types::Balance;
+use crate::MathError::{Overflow, ZeroReserve};
+
+use primitive_types::U256;
+use sp_arithmetic::Permill;
+use std::vec;
+
+pub(crate) const ONE: Balance = 1_000_000_000_000;
+
+fn sqrt_price(s: &str) -> U256 {
+    U256::from_dec_str(s).unwrap()
+}
+
+#[test]
+fn sqrt_price_at_tick_should_work() {
+    let cases = vec![
+        (0, Ok(U256::one() << 96), "Zero tick"),
+        (1, Ok(sqrt_price("79232123823359799118286999568")), "Tick one"),
+        (-1, Ok(sqrt_price("79224201403219477170569942574")), "Tick minus one"),
+        (60, Ok(sqrt_price("79466191966197645195421774833")), "Tick spacing"),
+        (MIN_TICK, Ok(MIN_SQRT_PRICE), "Min tick"),
+        (MAX_TICK, Ok(MAX_SQRT_PRICE), "Max tick"),
+        (MIN_TICK - 1, Err(Overflow), "Tick below min"),
+        (MAX_TICK + 1, Err(Overflow), "Tick above max"),
+    ];
+
+    for case in cases {
+        assert_eq!(calculate_sqrt_price_at_tick(case.0), case.1, "{}", case.2);
+    }
+}
+
+#[test]
+fn tick_at_sqrt_price_should_work() {
+    let cases = vec![
+        (U256::one() << 96, Ok(0), "Price one"),
+        ((U256::one() << 96) - 1, Ok(-1), "Price just below one"),
+        (sqrt_price("79232123823359799118286999568"), Ok(1), "Tick one"),
+        (
+            sqrt_price("79232123823359799118286999567"),
+            Ok(0),
+            "Just below tick one",
+        ),
+        (MIN_SQRT_PRICE, Ok(MIN_TICK), "Min sqrt price"),
+        (MAX_SQRT_PRICE - 1, Ok(MAX_TICK - 1), "Max sqrt price"),
+        (MIN_SQRT_PRICE - 1, Err(Overflow), "Sqrt price below min"),
+        (MAX_SQRT_PRICE, Err(Overflow), "Sqrt price at max"),
+    ];
+
+    for case in cases {
+        assert_eq!(calculate_tick_at_sqrt_price(case.0), case.1, "{}", case.2);
+    }
+}
+
+#[test]
+fn amount_deltas_should_work() {
+    let lower = calculate_sqrt_price_at_tick(0).unwrap();
+    let upper = calculate_sqrt_price_at_tick(1000).unwrap();
+    let liquidity = 1000 * ONE;
+
+    assert_eq!(
+        calculate_amount0_delta(lower, upper, liquidity, Rounding::Up),
+        Ok(48768197581279)
+    );
+    assert_eq!(
+        calculate_amount0_delta(upper, lower, liquidity, Rounding::Down),
+        Ok(48768197581278)
+    );
+    assert_eq!(
+        calculate_amount1_delta(lower, upper, liquidity, Rounding::Up),
+        Ok(51268468376767)
+    );
+    assert_eq!(
+        calculate_amount1_delta(upper, lower, liquidity, Rounding::Down),
+        Ok(51268468376766)
+    );
+
+    assert_eq!(calculate_amount0_delta(lower, lower, liquidity, Rounding::Up), Ok(0));
+    assert_eq!(calculate_amount1_delta(lower, upper, 0, Rounding::Up), Ok(0));
+    assert_eq!(
+        calculate_amount0_delta(U256::zero(), upper, liquidity, Rounding::Up),
+        Err(ZeroReserve)
+    );
+    assert_eq!(
+        calculate_amount0_delta(MIN_SQRT_PRICE, MAX_SQRT_PRICE, u128::MAX, Rounding::Up),
+        Err(Overflow)
+    );
+}
+
+#[test]
+fn next_sqrt_price_should_work() {
+    let price = calculate_sqrt_price_at_tick(0).unwrap();
+    let liquidity = 1000 * ONE;
+
+    assert_eq!(
+        calculate_next_sqrt_price_from_input(price, liquidity, ONE, true),
+        Ok(sqrt_price("79149013500763574019524425911"))
+    );
+    assert_eq!(
+        calculate_next_sqrt_price_from_input(price, liquidity, ONE, false),
+        Ok(sqrt_price("79307390676778601931137494286"))
+    );
+    assert_eq!(
+        calculate_next_sqrt_price_from_output(price, liquidity, ONE, true),
+        Ok(sqrt_price("79148934351750073255950406385"))
+    );
+    assert_eq!(
+        calculate_next_sqrt_price_from_output(price, liquidity, ONE, false),
+        Ok(sqrt_price("79307469984248586179723674011"))
+    );
+
+    assert_eq!(
+        calculate_next_sqrt_price_from_input(price, liquidity, 0, true),
+        Ok(price)
+    );
+    assert_eq!(
+        calculate_next_sqrt_price_from_input(price, 0, ONE, true),
+        Err(ZeroReserve)
+    );
+    assert_eq!(
+        calculate_next_sqrt_price_from_input(U256::zero(), liquidity, ONE, true),
+        Err(ZeroReserve)
+    );
+    assert_eq!(
+        calculate_next_sqrt_price_from_output(price, liquidity, liquidity, true),
+        Err(ZeroReserve)
+    );
+    assert_eq!(
+        calculate_next_sqrt_price_from_output(price, liquidity, liquidity, false),
+        Err(ZeroReserve)
+    );
+}
+
+#[test]
+fn swap_step_should_work() {
+    let price = calculate_sqrt_price_at_tick(0).unwrap();
+    let lower = calculate_sqrt_price_at_tick(-1000).unwrap();
+    let upper = calculate_sqrt_price_at_tick(1000).unwrap();
+    let liquidity = 1000 * ONE;
+    let fee = Permill::from_parts(3_000);
+
+    let cases = vec![
+        (
+            lower,
+            ONE,
+            true,
+            fee,
+            SwapStep {
+                sqrt_price_next: sqrt_price("79149250711305166342700278159"),
+                amount_in: 997000000000,
+                amount_out: 996006981039,
+                fee_amount: 3000000000,
+            },
+            "Exact in within range",
+        ),
+        (
+            lower,
+            1000 * ONE,
+            true,
+            fee,
+            SwapStep {
+                sqrt_price_next: lower,
+                amount_in: 51268468376767,
+                amount_out: 48768197581278,
+                fee_amount: 154268209760,
+            },
+            "Exact in reaching target",
+        ),
+        (
+            upper,
+            ONE,
+            false,
+            fee,
+            SwapStep {
+                sqrt_price_next: sqrt_price("79307469984248586179723674011"),
+                amount_in: 1001001001002,
+                amount_out: ONE,
+                fee_amount: 3012039121,
+            },
+            "Exact out within range",
+        ),
+        (
+            upper,
+            1000 * ONE,
+            false,
+            fee,
+            SwapStep {
+                sqrt_price_next: upper,
+                amount_in: 51268468376767,
+                amount_out: 48768197581278,
+                fee_amount: 154268209760,
+            },
+            "Exact out reaching target",
+        ),
+        (
+            upper,
+            ONE,
+            true,
+            Permill::from_parts(0),
+            SwapStep {
+                sqrt_price_next: sqrt_price("79307390676778601931137494286"),
+                amount_in: ONE,
+                amount_out: 999000999000,
+                fee_amount: 0,
+            },
+            "Zero fee",
+        ),
+    ];
+
+    for case in cases {
+        assert_eq!(
+            calculate_swap_step(price, case.0, liquidity, case.1, case.2, case.3),
+            Ok(case.4),
+            "{}",
+            case.5
+        );
+    }
+}
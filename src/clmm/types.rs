@@ -0,0 +1,22 @@
+use crate::types::Balance;
+use primitive_types::U256;
+
+/// Square root of a price represented as an unsigned Q64.96 fixed-point number.
+pub type SqrtPriceX96 = U256;
+/// Index of a price `1.0001^tick`.
+pub type Tick = i32;
+/// Virtual liquidity `L = sqrt(x * y)` of a position or of the pool in the current range.
+pub type Liquidity = u128;
+
+/// Result of a single swap step within one liquidity range.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SwapStep {
+    /// Sqrt price after the step
+    pub sqrt_price_next: SqrtPriceX96,
+    /// Amount to be sent to the pool, excluding fee
+    pub amount_in: Balance,
+    /// Amount to be received from the pool
+    pub amount_out: Balance,
+    /// Fee amount to be sent to the pool on top of the amount in
+    pub fee_amount: Balance,
+}
@@ -0,0 +1,148 @@
+//! Robust aggregation of recent oracle prices.
+//!
+//! A single EMA can be dragged by one large manipulated block. `PriceRing` keeps the most recent
+//! price samples to derive robust statistics (median, trimmed mean) and `clamp_price` limits how far
+//! an incoming price may deviate from the current EMA before it is integrated.
+
+use super::math::{calculate_new_by_integrating_incoming, EmaLiquidity, EmaPrice, EmaVolume};
+use crate::fraction;
+use crate::support::rational::Rounding;
+use crate::types::Fraction;
+
+/// Fixed-size ring buffer of the `N` most recent price samples.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PriceRing<const N: usize> {
+    samples: [EmaPrice; N],
+    len: usize,
+    next: usize,
+}
+
+impl<const N: usize> Default for PriceRing<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> PriceRing<N> {
+    pub const fn new() -> Self {
+        Self {
+            samples: [EmaPrice::zero(); N],
+            len: 0,
+            next: 0,
+        }
+    }
+
+    /// Add a sample, overwriting the oldest one if the ring is full.
+    pub fn push(&mut self, price: EmaPrice) {
+        if N == 0 {
+            return;
+        }
+        self.samples[self.next] = price;
+        self.next += 1;
+        if self.next == N {
+            self.next = 0;
+        }
+        self.len = self.len.saturating_add(1).min(N);
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    /// Iterate over the samples from oldest to newest.
+    pub fn iter(&self) -> impl Iterator<Item = &EmaPrice> {
+        let (newer, older) = self.samples[..self.len].split_at(if self.is_full() { self.next } else { 0 });
+        older.iter().chain(newer.iter())
+    }
+
+    /// Calculate the median of the samples.
+    /// For an even number of samples the two middle samples are averaged.
+    ///
+    /// Returns `None` if the ring is empty.
+    pub fn median(&self) -> Option<EmaPrice> {
+        let sorted = self.sorted();
+        let sorted = &sorted[..self.len];
+        let mid = sorted.len() / 2;
+        match sorted.len() {
+            0 => None,
+            len if len % 2 == 1 => Some(sorted[mid]),
+            _ => Some(mean(&sorted[mid - 1..=mid])),
+        }
+    }
+
+    /// Calculate the mean of the samples after discarding the `trim` lowest and the `trim`
+    /// highest samples.
+    ///
+    /// Returns `None` if no samples are left after trimming.
+    pub fn trimmed_mean(&self, trim: usize) -> Option<EmaPrice> {
+        let sorted = self.sorted();
+        let end = self.len.checked_sub(trim)?;
+        let kept = sorted.get(trim..end)?;
+        if kept.is_empty() {
+            return None;
+        }
+        Some(mean(kept))
+    }
+
+    /// Copy of the samples with the first `len` entries sorted ascending.
+    fn sorted(&self) -> [EmaPrice; N] {
+        let mut sorted = self.samples;
+        sorted[..self.len].sort_unstable();
+        sorted
+    }
+}
+
+/// Calculate the arithmetic mean of `prices`, dividing before adding so the sum cannot overflow.
+///
+/// Note: Rounds to nearest.
+fn mean(prices: &[EmaPrice]) -> EmaPrice {
+    debug_assert!(!prices.is_empty());
+    let weight = EmaPrice::new(1, prices.len() as u128);
+    prices.iter().fold(EmaPrice::zero(), |acc, price| {
+        acc.saturating_add(&price.saturating_mul(&weight, Rounding::Nearest), Rounding::Nearest)
+    })
+}
+
+/// Clamp `price` to the range `[reference * (1 - max_deviation), reference * (1 + max_deviation)]`.
+///
+/// Note: The bounds are rounded towards `reference`.
+pub fn clamp_price(price: EmaPrice, reference: EmaPrice, max_deviation: Fraction) -> EmaPrice {
+    let deviation = EmaPrice::new(max_deviation.to_bits(), fraction::DIV);
+    let lower_factor = EmaPrice::one().saturating_sub(&deviation, Rounding::Up);
+    let upper_factor = EmaPrice::one().saturating_add(&deviation, Rounding::Down);
+    let lower = reference.saturating_mul(&lower_factor, Rounding::Up);
+    let upper = reference.saturating_mul(&upper_factor, Rounding::Down);
+    if price < lower {
+        lower
+    } else if price > upper {
+        upper
+    } else {
+        price
+    }
+}
+
+/// Calculate the new oracle values by integrating `incoming` values with the `previous` oracle,
+/// clamping the incoming price to at most `max_deviation` away from the previous price first.
+/// See `calculate_new_by_integrating_incoming`.
+pub fn calculate_new_by_integrating_incoming_clamped(
+    previous: (EmaPrice, EmaVolume, EmaLiquidity),
+    incoming: (EmaPrice, EmaVolume, EmaLiquidity),
+    smoothing: Fraction,
+    max_deviation: Fraction,
+) -> (EmaPrice, EmaVolume, EmaLiquidity) {
+    let (incoming_price, incoming_volume, incoming_liquidity) = incoming;
+    let clamped_price = clamp_price(incoming_price, previous.0, max_deviation);
+    calculate_new_by_integrating_incoming(
+        previous,
+        (clamped_price, incoming_volume, incoming_liquidity),
+        smoothing,
+    )
+}
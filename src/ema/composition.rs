@@ -0,0 +1,104 @@
+use super::math::{EmaLiquidity, EmaPrice};
+use super::valuation::chain_prices;
+use crate::support::rational::Rounding;
+use crate::types::Balance;
+use sp_std::vec::Vec;
+
+/// A single hop of an oracle price path through a pool of the pair `(a, b)`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PriceHop {
+    /// EMA price of asset a denominated in asset b.
+    pub price: EmaPrice,
+    /// EMA liquidity of the pair as `(a, b)`.
+    pub liquidity: EmaLiquidity,
+    /// Whether the path goes from asset b to asset a, so the price needs to be inverted.
+    pub inverted: bool,
+}
+
+impl PriceHop {
+    pub fn new(price: EmaPrice, liquidity: EmaLiquidity) -> Self {
+        Self {
+            price,
+            liquidity,
+            inverted: false,
+        }
+    }
+
+    pub fn inverted(price: EmaPrice, liquidity: EmaLiquidity) -> Self {
+        Self {
+            price,
+            liquidity,
+            inverted: true,
+        }
+    }
+
+    /// Price of the hop in the direction of the path and the liquidity as `(in, out)`.
+    /// Returns `None` if a zero price needs to be inverted.
+    fn directed(&self) -> Option<(EmaPrice, EmaLiquidity)> {
+        let (a, b) = self.liquidity;
+        if self.inverted {
+            Some((self.price.reciprocal()?, (b, a)))
+        } else {
+            Some((self.price, (a, b)))
+        }
+    }
+}
+
+/// Compose the prices of a `path` of oracle entries into the price of the first asset of the
+/// path denominated in the last asset of the path, e.g. `A -> LRNA -> B`.
+///
+/// Returns the composed price and the minimal liquidity along the path. Liquidity of each hop is
+/// expressed in units of the first asset so it can be compared and used as a reliability signal.
+///
+/// The prices are chained by `chain_prices`, so the composed price is rounded the same way, see
+/// `compose_price_bounds` for guaranteed bounds.
+/// Returns `None` if the path is empty, a zero price needs to be inverted or an intermediate price
+/// is not representable.
+pub fn compose_prices(path: &[PriceHop]) -> Option<(EmaPrice, Balance)> {
+    if path.is_empty() {
+        return None;
+    }
+    let hops = path.iter().map(PriceHop::directed).collect::<Option<Vec<_>>>()?;
+    let prices: Vec<EmaPrice> = hops.iter().map(|(price, _)| *price).collect();
+
+    let mut min_liquidity = Balance::MAX;
+    for (i, (_, (liquidity_in, liquidity_out))) in hops.iter().enumerate() {
+        // `chain_prices` folds from the left, so the prefixes are the intermediate prices of the path
+        let price_in = chain_prices(&prices[..i])?;
+        let price_out = chain_prices(&prices[..=i])?;
+        min_liquidity = min_liquidity
+            .min(liquidity_in_first_asset(*liquidity_in, price_in))
+            .min(liquidity_in_first_asset(*liquidity_out, price_out));
+    }
+    Some((chain_prices(&prices)?, min_liquidity))
+}
+
+/// Compose the prices of a `path` like `compose_prices` but round down and up respectively.
+/// The exact composed price is guaranteed to be within the returned `(lower, upper)` bounds.
+///
+/// Returns `None` under the same conditions as `compose_prices`.
+pub fn compose_price_bounds(path: &[PriceHop]) -> Option<(EmaPrice, EmaPrice)> {
+    if path.is_empty() {
+        return None;
+    }
+    path.iter()
+        .try_fold((EmaPrice::one(), EmaPrice::one()), |(lower, upper), hop| {
+            // inversion is exact, so it does not widen the bounds
+            let (hop_price, _) = hop.directed()?;
+            Some((
+                lower.checked_mul(&hop_price, Rounding::Down)?,
+                upper.checked_mul(&hop_price, Rounding::Up)?,
+            ))
+        })
+}
+
+/// Convert `liquidity` of an asset into units of the first asset of a path.
+/// `price` is the price of the first asset denominated in the asset of `liquidity`.
+///
+/// Note: Rounds down and saturates, a zero price results in the largest value.
+fn liquidity_in_first_asset(liquidity: Balance, price: EmaPrice) -> Balance {
+    price
+        .reciprocal()
+        .and_then(|reciprocal| reciprocal.mul_balance(liquidity, Rounding::Down))
+        .unwrap_or(Balance::MAX)
+}
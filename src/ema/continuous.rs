@@ -0,0 +1,98 @@
+//! Time-weighted exponential moving averages.
+//!
+//! Instead of decaying by a fixed smoothing factor per block iteration, the values decay based on
+//! the elapsed time and a `half_life`: after `half_life` has elapsed the previous value has half
+//! of its original weight. Elapsed time and half-life need to be given in the same unit
+//! (e.g. milliseconds).
+
+use super::math::{
+    balance_weighted_average, liquidity_weighted_average, price_weighted_average, volume_weighted_average,
+    EmaLiquidity, EmaPrice, EmaVolume,
+};
+use crate::transcendental::half_life_decay;
+use crate::types::{Balance, Fraction};
+use crate::MathError;
+use crate::MathError::Overflow;
+
+use num_traits::One;
+
+/// Calculate the smoothing factor for `elapsed` time given the `half_life` of the EMA:
+/// `smoothing = 1 - 2^(-elapsed / half_life)`.
+///
+/// A `half_life` of zero means the previous value has no weight left, so the smoothing is `1`.
+///
+/// Example:
+/// `smoothing_from_half_life(12_000, 6_000) = Ok(1 - 2^(-2)) = Ok(0.75)`
+pub fn smoothing_from_half_life(elapsed: u64, half_life: u64) -> Result<Fraction, MathError> {
+    let complement: Fraction = half_life_decay(elapsed.into(), half_life.into()).map_err(|_| Overflow)?;
+    Fraction::one().checked_sub(complement).ok_or(Overflow)
+}
+
+/// Calculate the current oracle values from the `outdated` and `update_with` values with the
+/// old values being `elapsed` time out of date.
+/// Returns an error if the smoothing factor cannot be calculated.
+///
+/// Note: The volume is always updated with zero values so it is not a parameter.
+pub fn update_outdated_to_current(
+    elapsed: u64,
+    half_life: u64,
+    outdated: (EmaPrice, EmaVolume, EmaLiquidity),
+    update_with: (EmaPrice, EmaLiquidity),
+) -> Result<(EmaPrice, EmaVolume, EmaLiquidity), MathError> {
+    let (prev_price, prev_volume, prev_liquidity) = outdated;
+    let (incoming_price, incoming_liquidity) = update_with;
+    let smoothing = smoothing_from_half_life(elapsed, half_life)?;
+    let new_price = price_weighted_average(prev_price, incoming_price, smoothing);
+    let new_volume = volume_weighted_average(prev_volume, (0, 0, 0, 0), smoothing);
+    let new_liquidity = liquidity_weighted_average(prev_liquidity, incoming_liquidity, smoothing);
+    Ok((new_price, new_volume, new_liquidity))
+}
+
+/// Calculate the time-weighted exponential moving average for the given prices.
+/// `elapsed` is the time since `prev` was last updated.
+/// `prev` is the previous oracle value, `incoming` is the new value to integrate.
+pub fn price_ema(elapsed: u64, half_life: u64, prev: EmaPrice, incoming: EmaPrice) -> Result<EmaPrice, MathError> {
+    Ok(price_weighted_average(
+        prev,
+        incoming,
+        smoothing_from_half_life(elapsed, half_life)?,
+    ))
+}
+
+/// Calculate the time-weighted exponential moving average for the given balances.
+/// `elapsed` is the time since `prev` was last updated.
+/// `prev` is the previous oracle value, `incoming` is the new value to integrate.
+pub fn balance_ema(elapsed: u64, half_life: u64, prev: Balance, incoming: Balance) -> Result<Balance, MathError> {
+    Ok(balance_weighted_average(
+        prev,
+        incoming,
+        smoothing_from_half_life(elapsed, half_life)?,
+    ))
+}
+
+/// Calculate the time-weighted exponential moving average for the given volumes.
+/// `elapsed` is the time since `prev` was last updated.
+/// `prev` is the previous oracle value; the incoming value is always zero.
+pub fn volume_ema(elapsed: u64, half_life: u64, prev: EmaVolume) -> Result<EmaVolume, MathError> {
+    Ok(volume_weighted_average(
+        prev,
+        (0, 0, 0, 0),
+        smoothing_from_half_life(elapsed, half_life)?,
+    ))
+}
+
+/// Calculate the time-weighted exponential moving average for the given liquidity.
+/// `elapsed` is the time since `prev` was last updated.
+/// `prev` is the previous oracle value, `incoming` is the new value to integrate.
+pub fn liquidity_ema(
+    elapsed: u64,
+    half_life: u64,
+    prev: EmaLiquidity,
+    incoming: EmaLiquidity,
+) -> Result<EmaLiquidity, MathError> {
+    Ok(liquidity_weighted_average(
+        prev,
+        incoming,
+        smoothing_from_half_life(elapsed, half_life)?,
+    ))
+}
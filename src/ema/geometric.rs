@@ -0,0 +1,146 @@
+//! Geometric exponential moving average of prices.
+//!
+//! The arithmetic EMA of prices is asymmetric: the EMA of `A/B` is not the reciprocal of the EMA of
+//! `B/A`. The geometric EMA averages the logarithms of the prices instead, i.e.
+//! `new = prev^(1 - weight) * incoming^weight`, which is symmetric under inversion.
+
+use super::math::{
+    exp_smoothing, liquidity_weighted_average, price_weighted_average, round_to_rational, volume_weighted_average,
+    EmaLiquidity, EmaPrice, EmaVolume,
+};
+use crate::support::rational::Rounding;
+use crate::transcendental::{exp, log2};
+use crate::types::Fraction;
+
+use fixed::types::U64F64;
+use num_traits::One;
+use primitive_types::{U256, U512};
+
+/// Calculate the new oracle values by integrating `incoming` values with the `previous` oracle.
+/// The price is averaged geometrically, volume and liquidity arithmetically.
+pub fn calculate_new_by_integrating_incoming_geometric(
+    previous: (EmaPrice, EmaVolume, EmaLiquidity),
+    incoming: (EmaPrice, EmaVolume, EmaLiquidity),
+    smoothing: Fraction,
+) -> (EmaPrice, EmaVolume, EmaLiquidity) {
+    let (prev_price, prev_volume, prev_liquidity) = previous;
+    let (incoming_price, incoming_volume, incoming_liquidity) = incoming;
+    let new_price = geometric_price_weighted_average(prev_price, incoming_price, smoothing);
+    let new_volume = volume_weighted_average(prev_volume, incoming_volume, smoothing);
+    let new_liquidity = liquidity_weighted_average(prev_liquidity, incoming_liquidity, smoothing);
+    (new_price, new_volume, new_liquidity)
+}
+
+/// Calculate the current oracle values from the `outdated` and `update_with` values using the
+/// `smoothing` factor with the old values being `iterations` out of date.
+/// The price is averaged geometrically, volume and liquidity arithmetically.
+///
+/// Note: The volume is always updated with zero values so it is not a parameter.
+pub fn update_outdated_to_current_geometric(
+    iterations: u32,
+    outdated: (EmaPrice, EmaVolume, EmaLiquidity),
+    update_with: (EmaPrice, EmaLiquidity),
+    smoothing: Fraction,
+) -> (EmaPrice, EmaVolume, EmaLiquidity) {
+    let (prev_price, prev_volume, prev_liquidity) = outdated;
+    let (incoming_price, incoming_liquidity) = update_with;
+    let smoothing = exp_smoothing(smoothing, iterations);
+    let new_price = geometric_price_weighted_average(prev_price, incoming_price, smoothing);
+    let new_volume = volume_weighted_average(prev_volume, (0, 0, 0, 0), smoothing);
+    let new_liquidity = liquidity_weighted_average(prev_liquidity, incoming_liquidity, smoothing);
+    (new_price, new_volume, new_liquidity)
+}
+
+/// Calculate the iterated geometric exponential moving average for the given prices.
+/// `iterations` is the number of iterations of the EMA to calculate.
+/// `prev` is the previous oracle value, `incoming` is the new value to integrate.
+/// `smoothing` is the smoothing factor of the EMA.
+pub fn iterated_geometric_price_ema(
+    iterations: u32,
+    prev: EmaPrice,
+    incoming: EmaPrice,
+    smoothing: Fraction,
+) -> EmaPrice {
+    geometric_price_weighted_average(prev, incoming, exp_smoothing(smoothing, iterations))
+}
+
+/// Calculate a weighted geometric average for the given prices:
+/// `prev^(1 - weight) * incoming^weight = prev * (incoming / prev)^weight`.
+/// `prev` is the previous oracle value, `incoming` is the new value to integrate.
+/// `weight` is how much weight to give the new value.
+///
+/// The factor `(incoming / prev)^weight` is always determined for a ratio `>= 1` and then
+/// multiplied or divided, so averaging the reciprocal prices results in the reciprocal average.
+///
+/// Note: Zero prices have no logarithm, so they fall back to `price_weighted_average`.
+pub fn geometric_price_weighted_average(prev: EmaPrice, incoming: EmaPrice, weight: Fraction) -> EmaPrice {
+    debug_assert!(weight <= Fraction::one(), "weight must be <= 1");
+    if weight.is_zero() || prev == incoming {
+        return prev;
+    } else if weight == Fraction::one() {
+        return incoming;
+    } else if prev.is_zero() || incoming.is_zero() {
+        return price_weighted_average(prev, incoming, weight);
+    }
+
+    let rising = incoming >= prev;
+    // ratio = max(incoming / prev, prev / incoming) >= 1
+    let (larger, smaller) = if rising { (incoming, prev) } else { (prev, incoming) };
+    let ratio_n = U256::from(larger.n).saturating_mul(U256::from(smaller.d));
+    let ratio_d = U256::from(larger.d).saturating_mul(U256::from(smaller.n));
+
+    let (shift, factor) = match weighted_power(ratio_n, ratio_d, weight) {
+        Some(power) => power,
+        None => return price_weighted_average(prev, incoming, weight),
+    };
+
+    // prev * factor * 2^shift or prev / (factor * 2^shift)
+    let (prev_n, prev_d) = (U512::from(prev.n), U512::from(prev.d));
+    let factor_n = U512::from(factor.to_bits());
+    let factor_d = U512::one() << U64F64::FRAC_NBITS as usize;
+    let (n, d) = if rising {
+        (
+            prev_n.saturating_mul(factor_n) << shift,
+            prev_d.saturating_mul(factor_d),
+        )
+    } else {
+        (
+            prev_n.saturating_mul(factor_d),
+            prev_d.saturating_mul(factor_n) << shift,
+        )
+    };
+    round_to_rational((n, d), Rounding::Nearest)
+}
+
+/// Calculate `(n / d)^weight` for `n >= d > 0` as `factor * 2^shift` with `factor` in `[1, 2)`.
+///
+/// Uses `log2(n / d) = k + log2(m)` with `n / d = 2^k * m` and `m` in `[1, 2)` so that the
+/// logarithm can be calculated for any ratio of `U256` values. `weight * k` is calculated exactly,
+/// so only `log2(m)` and the final `exp` are approximated.
+fn weighted_power(n: U256, d: U256, weight: Fraction) -> Option<(usize, U64F64)> {
+    debug_assert!(n >= d && !d.is_zero());
+    let frac_nbits = Fraction::FRAC_NBITS as usize;
+
+    let mut k = n.bits().saturating_sub(d.bits());
+    if (U512::from(d) << k) > U512::from(n) {
+        k = k.checked_sub(1)?;
+    }
+    // m = n / (d * 2^k) with 64 fractional bits
+    let m = (U512::from(n) << U64F64::FRAC_NBITS as usize) / (U512::from(d) << k);
+    let m = U64F64::from_bits(u128::try_from(m).ok()?);
+    let (log2_m, negative) = log2::<U64F64, U64F64>(m).ok()?;
+    debug_assert!(!negative);
+
+    // weight * log2(ratio) with the fractional bits of `Fraction`
+    let weight = U256::from(weight.to_bits());
+    let weighted_k = weight.checked_mul(U256::from(k))?;
+    let weighted_log2_m = weight.checked_mul(U256::from(log2_m.to_bits()))? >> U64F64::FRAC_NBITS as usize;
+    let exponent = weighted_k.checked_add(weighted_log2_m)?;
+
+    // ratio^weight = 2^shift * 2^rem = 2^shift * e^(rem * ln(2))
+    let shift = exponent >> frac_nbits;
+    let rem = (exponent - (shift << frac_nbits)) >> (frac_nbits - U64F64::FRAC_NBITS as usize);
+    let rem = U64F64::from_bits(u128::try_from(rem).ok()?);
+    let factor = exp::<U64F64, U64F64>(rem.checked_mul(U64F64::from_num(fixed::consts::LN_2))?, false).ok()?;
+    Some((usize::try_from(shift).ok()?, factor))
+}
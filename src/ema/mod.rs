@@ -1,6 +1,16 @@
+pub mod aggregate;
+mod composition;
+pub mod continuous;
+mod geometric;
 mod math;
+mod valuation;
+mod volatility;
 
 #[cfg(test)]
 mod tests;
 
+pub use composition::*;
+pub use geometric::*;
 pub use math::*;
+pub use valuation::*;
+pub use volatility::*;
@@ -0,0 +1,144 @@
+use crate::ema::aggregate::*;
+use crate::ema::{calculate_new_by_integrating_incoming, EmaPrice};
+use crate::fraction;
+
+use proptest::prelude::*;
+use rug::Rational;
+
+#[test]
+fn price_ring_should_keep_most_recent_samples() {
+    let mut ring = PriceRing::<3>::new();
+    assert!(ring.is_empty());
+    assert_eq!(ring.median(), None);
+    assert_eq!(ring.trimmed_mean(0), None);
+
+    ring.push(EmaPrice::new(1, 1));
+    ring.push(EmaPrice::new(2, 1));
+    assert_eq!(ring.len(), 2);
+    assert!(!ring.is_full());
+    assert_eq!(
+        ring.iter().copied().collect::<Vec<_>>(),
+        vec![EmaPrice::new(1, 1), EmaPrice::new(2, 1)]
+    );
+
+    ring.push(EmaPrice::new(3, 1));
+    ring.push(EmaPrice::new(4, 1));
+    assert_eq!(ring.len(), 3);
+    assert!(ring.is_full());
+    assert_eq!(
+        ring.iter().copied().collect::<Vec<_>>(),
+        vec![EmaPrice::new(2, 1), EmaPrice::new(3, 1), EmaPrice::new(4, 1)]
+    );
+
+    let mut empty = PriceRing::<0>::new();
+    empty.push(EmaPrice::new(1, 1));
+    assert!(empty.is_empty());
+    assert_eq!(empty.median(), None);
+}
+
+#[test]
+fn median_should_work() {
+    let mut ring = PriceRing::<4>::default();
+    ring.push(EmaPrice::new(5, 1));
+    assert_eq!(ring.median(), Some(EmaPrice::new(5, 1)));
+    ring.push(EmaPrice::new(1, 1));
+    ring.push(EmaPrice::new(1_000, 1));
+    // a single outlier does not move the median
+    assert_eq!(ring.median(), Some(EmaPrice::new(5, 1)));
+    ring.push(EmaPrice::new(2, 1));
+    // average of 2 and 5
+    assert_eq!(Rational::from(ring.median().unwrap()), Rational::from((7, 2)));
+}
+
+#[test]
+fn trimmed_mean_should_work() {
+    let mut ring = PriceRing::<5>::new();
+    for price in [3, 1, 1_000, 2, 4] {
+        ring.push(EmaPrice::new(price, 1));
+    }
+    assert_eq!(Rational::from(ring.trimmed_mean(0).unwrap()), Rational::from(202));
+    // discards 1 and 1_000
+    assert_eq!(Rational::from(ring.trimmed_mean(1).unwrap()), Rational::from(3));
+    assert_eq!(Rational::from(ring.trimmed_mean(2).unwrap()), Rational::from(3));
+    assert_eq!(ring.trimmed_mean(3), None);
+}
+
+#[test]
+fn clamp_price_should_work() {
+    let reference = EmaPrice::new(100, 1);
+    let max_deviation = fraction::frac(1, 8);
+    assert_eq!(
+        clamp_price(EmaPrice::new(105, 1), reference, max_deviation),
+        EmaPrice::new(105, 1)
+    );
+    assert_eq!(
+        Rational::from(clamp_price(EmaPrice::new(200, 1), reference, max_deviation)),
+        Rational::from((225, 2))
+    );
+    assert_eq!(
+        Rational::from(clamp_price(EmaPrice::new(1, 1), reference, max_deviation)),
+        Rational::from((175, 2))
+    );
+    // deviation of more than 100% allows any lower price
+    assert_eq!(
+        clamp_price(EmaPrice::zero(), reference, fraction::frac(3, 2)),
+        EmaPrice::zero()
+    );
+}
+
+#[test]
+fn clamped_update_should_limit_manipulation() {
+    let previous = (EmaPrice::new(100, 1), (0, 0, 0, 0), (1_000, 1_000));
+    let manipulated = (EmaPrice::new(10_000, 1), (5, 5, 5, 5), (2_000, 2_000));
+    let smoothing = fraction::frac(1, 2);
+
+    let (price, volume, liquidity) =
+        calculate_new_by_integrating_incoming_clamped(previous, manipulated, smoothing, fraction::frac(1, 8));
+    // clamped to 112.5
+    assert_eq!(Rational::from(price), Rational::from((425, 4)));
+    let unclamped = calculate_new_by_integrating_incoming(previous, manipulated, smoothing);
+    assert_eq!((volume, liquidity), (unclamped.1, unclamped.2));
+    assert!(unclamped.0 > price);
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(1_000))]
+    #[test]
+    fn median_and_trimmed_mean_are_within_samples(
+        prices in prop::collection::vec((1..u128::MAX, 1..u128::MAX), 1..20),
+        trim in 0_usize..5,
+    ) {
+        let mut ring = PriceRing::<8>::new();
+        for (n, d) in prices {
+            ring.push(EmaPrice::new(n, d));
+        }
+        let min = ring.iter().min().copied().unwrap();
+        let max = ring.iter().max().copied().unwrap();
+        // rounding can move the averages slightly beyond the samples
+        let tolerance = Rational::from(max) / Rational::from(1_u128 << 60);
+        let (lower, upper) = (Rational::from(min) - tolerance.clone(), Rational::from(max) + tolerance);
+        let median = Rational::from(ring.median().unwrap());
+        prop_assert!(lower <= median && median <= upper);
+        if let Some(mean) = ring.trimmed_mean(trim) {
+            let mean = Rational::from(mean);
+            prop_assert!(lower <= mean && mean <= upper);
+        }
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(1_000))]
+    #[test]
+    fn clamped_price_is_within_deviation(
+        (price, reference) in ((0..u64::MAX as u128, 1..u64::MAX as u128), (1..u64::MAX as u128, 1..u64::MAX as u128)),
+        max_deviation in (0..fraction::DIV).prop_map(crate::types::Fraction::from_bits),
+    ) {
+        let (price, reference) = (EmaPrice::new(price.0, price.1), EmaPrice::new(reference.0, reference.1));
+        let clamped = clamp_price(price, reference, max_deviation);
+        let reference = Rational::from(reference);
+        let deviation = crate::test_utils::fraction_to_high_precision(max_deviation);
+        let lower = reference.clone() * (Rational::from(1) - deviation.clone());
+        let upper = reference * (Rational::from(1) + deviation);
+        prop_assert!(Rational::from(clamped) >= lower && Rational::from(clamped) <= upper);
+    }
+}
@@ -0,0 +1,133 @@
+use crate::ema::*;
+use crate::test_utils::prop_assert_rational_relative_approx_eq;
+use crate::types::Balance;
+
+use proptest::prelude::*;
+use rug::Rational;
+
+const ONE: Balance = 1_000_000_000_000;
+
+#[test]
+fn compose_prices_should_work() {
+    // 1 A = 4 LRNA with 100 A and 400 LRNA
+    let a_lrna = PriceHop::new(EmaPrice::new(4, 1), (100 * ONE, 400 * ONE));
+    // 1 B = 2 LRNA with 1_000 B and 2_000 LRNA
+    let b_lrna = PriceHop::inverted(EmaPrice::new(2, 1), (1_000 * ONE, 2_000 * ONE));
+
+    let (price, min_liquidity) = compose_prices(&[a_lrna, b_lrna]).unwrap();
+    // 1 A = 2 B
+    assert_eq!(Rational::from(price), Rational::from(2));
+    // the A pool is the shallowest one
+    assert_eq!(min_liquidity, 100 * ONE);
+
+    // reversing the path inverts the price
+    let lrna_a = PriceHop::inverted(EmaPrice::new(4, 1), (100 * ONE, 400 * ONE));
+    let lrna_b = PriceHop::new(EmaPrice::new(2, 1), (1_000 * ONE, 2_000 * ONE));
+    let (price, min_liquidity) = compose_prices(&[lrna_b, lrna_a]).unwrap();
+    assert_eq!(Rational::from(price), Rational::from((1, 2)));
+    // 100 A are worth 200 B
+    assert_eq!(min_liquidity, 200 * ONE);
+}
+
+#[test]
+fn compose_prices_should_report_min_liquidity_in_first_asset() {
+    let a_lrna = PriceHop::new(EmaPrice::new(4, 1), (100 * ONE, 400 * ONE));
+    // shallow pool: 10 B and 20 LRNA is worth 5 A
+    let b_lrna = PriceHop::inverted(EmaPrice::new(2, 1), (10 * ONE, 20 * ONE));
+    let (_, min_liquidity) = compose_prices(&[a_lrna, b_lrna]).unwrap();
+    assert_eq!(min_liquidity, 5 * ONE);
+}
+
+#[test]
+fn compose_prices_should_fail_for_invalid_paths() {
+    assert_eq!(compose_prices(&[]), None);
+    assert_eq!(compose_price_bounds(&[]), None);
+    // zero price cannot be inverted
+    let hop = PriceHop::inverted(EmaPrice::zero(), (ONE, 0));
+    assert_eq!(compose_prices(&[hop]), None);
+    assert_eq!(compose_price_bounds(&[hop]), None);
+    // not representable
+    let hop = PriceHop::new(EmaPrice::new(u128::MAX, 1), (ONE, ONE));
+    assert_eq!(compose_prices(&[hop, hop]), None);
+}
+
+#[test]
+fn compose_price_bounds_should_be_exact_for_representable_prices() {
+    let a_lrna = PriceHop::new(EmaPrice::new(4, 1), (ONE, ONE));
+    let b_lrna = PriceHop::inverted(EmaPrice::new(3, 1), (ONE, ONE));
+    let (lower, upper) = compose_price_bounds(&[a_lrna, b_lrna]).unwrap();
+    assert_eq!(Rational::from(lower), Rational::from((4, 3)));
+    assert_eq!(Rational::from(upper), Rational::from((4, 3)));
+}
+
+fn hop() -> impl Strategy<Value = PriceHop> {
+    (1..u128::MAX, 1..u128::MAX, (1..u128::MAX, 1..u128::MAX), any::<bool>()).prop_map(|(n, d, liquidity, inverted)| {
+        PriceHop {
+            price: EmaPrice::new(n, d),
+            liquidity,
+            inverted,
+        }
+    })
+}
+
+fn exact_price(path: &[PriceHop]) -> Rational {
+    path.iter().fold(Rational::from(1), |acc, hop| {
+        let price = Rational::from(hop.price);
+        if hop.inverted {
+            acc / price
+        } else {
+            acc * price
+        }
+    })
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(1_000))]
+    #[test]
+    fn composed_price_is_within_bounds(path in prop::collection::vec(hop(), 1..6)) {
+        let exact = exact_price(&path);
+        if let Some((lower, upper)) = compose_price_bounds(&path) {
+            prop_assert!(Rational::from(lower) <= exact);
+            prop_assert!(Rational::from(upper) >= exact);
+            if let Some((price, _)) = compose_prices(&path) {
+                prop_assert!(lower <= price && price <= upper);
+            }
+        }
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(1_000))]
+    #[test]
+    fn composed_price_error_is_bounded(path in prop::collection::vec(hop(), 1..6)) {
+        // skip paths whose intermediate values are out of the precise range
+        for i in 1..=path.len() {
+            let intermediate = exact_price(&path[..i]);
+            prop_assume!(intermediate >= Rational::from((1, 1_u128 << 60)));
+            prop_assume!(intermediate <= Rational::from(u128::MAX));
+        }
+        let (price, _) = compose_prices(&path).unwrap();
+        let tolerance = Rational::from((path.len() as u128, 1_u128 << 62));
+        prop_assert_rational_relative_approx_eq!(Rational::from(price), exact_price(&path), tolerance);
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(1_000))]
+    #[test]
+    fn inverted_path_has_reciprocal_price(path in prop::collection::vec(hop(), 1..6)) {
+        let reversed: Vec<_> = path
+            .iter()
+            .rev()
+            .map(|hop| PriceHop { inverted: !hop.inverted, ..*hop })
+            .collect();
+        let ((lower, upper), (reversed_lower, reversed_upper)) =
+            match (compose_price_bounds(&path), compose_price_bounds(&reversed)) {
+                (Some(bounds), Some(reversed_bounds)) => (bounds, reversed_bounds),
+                _ => return Ok(()),
+            };
+        // the bounds of the reversed path contain the reciprocal of the exact price
+        prop_assert!(Rational::from(lower) * Rational::from(reversed_lower) <= 1);
+        prop_assert!(Rational::from(upper) * Rational::from(reversed_upper) >= 1);
+    }
+}
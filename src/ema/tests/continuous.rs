@@ -0,0 +1,134 @@
+use crate::ema::continuous::*;
+use crate::ema::{exp_smoothing, iterated_balance_ema, iterated_price_ema, EmaPrice};
+use crate::fraction;
+use crate::test_utils::{assert_rational_approx_eq, fraction_to_high_precision, prop_assert_rational_approx_eq};
+use crate::types::Fraction;
+
+use num_traits::{One, Zero};
+use proptest::prelude::*;
+use rug::{Float, Rational};
+
+/// 6 seconds in milliseconds
+const BLOCK_TIME: u64 = 6_000;
+
+fn tolerance() -> Rational {
+    Rational::from((1, 1_000_000_000_000_000_u128))
+}
+
+#[test]
+fn smoothing_from_half_life_works() {
+    assert_eq!(smoothing_from_half_life(0, BLOCK_TIME).unwrap(), Fraction::zero());
+    assert_eq!(
+        smoothing_from_half_life(BLOCK_TIME, BLOCK_TIME).unwrap(),
+        fraction::frac(1, 2)
+    );
+    assert_eq!(
+        smoothing_from_half_life(2 * BLOCK_TIME, BLOCK_TIME).unwrap(),
+        fraction::frac(3, 4)
+    );
+    assert_eq!(
+        smoothing_from_half_life(10 * BLOCK_TIME, BLOCK_TIME).unwrap(),
+        fraction::frac(1023, 1024)
+    );
+    // no weight is left for the previous value
+    assert_eq!(smoothing_from_half_life(BLOCK_TIME, 0).unwrap(), Fraction::one());
+    assert_eq!(
+        smoothing_from_half_life(200 * BLOCK_TIME, BLOCK_TIME).unwrap(),
+        Fraction::one()
+    );
+    assert_eq!(smoothing_from_half_life(u64::MAX, 1).unwrap(), Fraction::one());
+
+    // 1 - 2^(-1/2)
+    let expected = Rational::one() - Float::with_val(256, -0.5).exp2().to_rational().unwrap();
+    assert_rational_approx_eq!(
+        fraction_to_high_precision(smoothing_from_half_life(BLOCK_TIME / 2, BLOCK_TIME).unwrap()),
+        expected,
+        tolerance()
+    );
+}
+
+#[test]
+fn continuous_smoothing_should_agree_with_iterations_for_whole_blocks() {
+    let half_life = 600 * BLOCK_TIME;
+    let per_block = smoothing_from_half_life(BLOCK_TIME, half_life).unwrap();
+    for blocks in [1_u32, 2, 10, 100, 599, 600, 601, 14_400] {
+        let iterated = exp_smoothing(per_block, blocks);
+        let continuous = smoothing_from_half_life(u64::from(blocks) * BLOCK_TIME, half_life).unwrap();
+        assert_rational_approx_eq!(
+            fraction_to_high_precision(iterated),
+            fraction_to_high_precision(continuous),
+            tolerance(),
+            "continuous smoothing should match iterated smoothing"
+        );
+    }
+}
+
+#[test]
+fn continuous_ema_should_agree_with_iterated_ema() {
+    let half_life = 100 * BLOCK_TIME;
+    let per_block = smoothing_from_half_life(BLOCK_TIME, half_life).unwrap();
+    let blocks = 250;
+    let elapsed = u64::from(blocks) * BLOCK_TIME;
+
+    let prev_price = EmaPrice::new(1_000, 1);
+    let incoming_price = EmaPrice::new(2_000, 3);
+    let iterated = iterated_price_ema(blocks, prev_price, incoming_price, per_block);
+    let continuous = price_ema(elapsed, half_life, prev_price, incoming_price).unwrap();
+    assert_rational_approx_eq!(
+        Rational::from(iterated),
+        Rational::from(continuous),
+        Rational::from((1, 1_000_000_000_000_u128))
+    );
+
+    let iterated = iterated_balance_ema(blocks, 1_000_000_000_000, 4_000_000_000_000, per_block);
+    let continuous = balance_ema(elapsed, half_life, 1_000_000_000_000, 4_000_000_000_000).unwrap();
+    assert!(iterated.abs_diff(continuous) <= 1);
+}
+
+#[test]
+fn update_outdated_to_current_should_use_elapsed_time() {
+    let outdated = (EmaPrice::new(1, 1), (100, 200, 300, 400), (1_000, 2_000));
+    let update_with = (EmaPrice::new(3, 1), (3_000, 4_000));
+    let (price, volume, liquidity) = update_outdated_to_current(BLOCK_TIME, BLOCK_TIME, outdated, update_with).unwrap();
+    assert_eq!(Rational::from(price), Rational::from(2));
+    assert_eq!(volume, (50, 100, 150, 200));
+    assert_eq!(liquidity, (2_000, 3_000));
+    assert_eq!(volume_ema(BLOCK_TIME, BLOCK_TIME, outdated.1), Ok(volume));
+    assert_eq!(
+        liquidity_ema(BLOCK_TIME, BLOCK_TIME, outdated.2, update_with.1),
+        Ok(liquidity)
+    );
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(1_000))]
+    #[test]
+    fn continuous_smoothing_matches_iterations(
+        blocks in 1_u32..100_000,
+        half_life_blocks in 1_u64..20_000,
+    ) {
+        let half_life = half_life_blocks * BLOCK_TIME;
+        let per_block = smoothing_from_half_life(BLOCK_TIME, half_life).unwrap();
+        let iterated = exp_smoothing(per_block, blocks);
+        let continuous = smoothing_from_half_life(u64::from(blocks) * BLOCK_TIME, half_life).unwrap();
+        prop_assert_rational_approx_eq!(
+            fraction_to_high_precision(iterated),
+            fraction_to_high_precision(continuous),
+            Rational::from((1, 100_000_000_000_000_u128))
+        );
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(1_000))]
+    #[test]
+    fn continuous_smoothing_is_precise(
+        elapsed in 0_u64..1_000_000_000,
+        half_life in 1_u64..100_000_000,
+    ) {
+        let smoothing = smoothing_from_half_life(elapsed, half_life).unwrap();
+        let exponent = -Float::with_val(256, Rational::from((elapsed, half_life)));
+        let expected = Rational::one() - exponent.exp2().to_rational().unwrap();
+        prop_assert_rational_approx_eq!(fraction_to_high_precision(smoothing), expected, tolerance());
+    }
+}
@@ -0,0 +1,115 @@
+use super::*;
+
+use crate::test_utils::assert_rational_relative_approx_eq;
+
+use num_traits::Zero;
+
+#[test]
+fn geometric_price_weighted_average_works() {
+    let tolerance = Rational::from((1, 1e17 as u128));
+
+    let average = geometric_price_weighted_average(EmaPrice::new(1, 1), EmaPrice::new(4, 1), fraction::frac(1, 2));
+    assert_rational_relative_approx_eq!(Rational::from(average), Rational::from(2), tolerance.clone());
+
+    let average = geometric_price_weighted_average(EmaPrice::new(4, 1), EmaPrice::new(1, 1), fraction::frac(1, 2));
+    assert_rational_relative_approx_eq!(Rational::from(average), Rational::from(2), tolerance.clone());
+
+    // 1 * 1024^(1/10) = 2
+    let average = geometric_price_weighted_average(EmaPrice::new(1, 1), EmaPrice::new(1024, 1), fraction::frac(1, 10));
+    assert_rational_relative_approx_eq!(Rational::from(average), Rational::from(2), tolerance.clone());
+
+    // extreme ratios
+    let average = geometric_price_weighted_average(
+        EmaPrice::new(1, u128::MAX),
+        EmaPrice::new(u128::MAX, 1),
+        fraction::frac(1, 2),
+    );
+    assert_rational_relative_approx_eq!(Rational::from(average), Rational::from(1), tolerance);
+}
+
+#[test]
+fn geometric_price_weighted_average_edge_cases() {
+    let (prev, incoming) = (EmaPrice::new(1, 3), EmaPrice::new(7, 5));
+    assert_eq!(geometric_price_weighted_average(prev, incoming, Fraction::zero()), prev);
+    assert_eq!(
+        geometric_price_weighted_average(prev, incoming, Fraction::one()),
+        incoming
+    );
+    assert_eq!(geometric_price_weighted_average(prev, prev, fraction::frac(1, 3)), prev);
+    // zero prices fall back to the arithmetic average
+    let weight = fraction::frac(1, 4);
+    assert_eq!(
+        geometric_price_weighted_average(EmaPrice::zero(), incoming, weight),
+        price_weighted_average(EmaPrice::zero(), incoming, weight)
+    );
+    assert_eq!(
+        geometric_price_weighted_average(prev, EmaPrice::zero(), weight),
+        price_weighted_average(prev, EmaPrice::zero(), weight)
+    );
+}
+
+#[test]
+fn geometric_price_weighted_average_is_reciprocal_symmetric() {
+    let (prev, incoming) = (EmaPrice::new(3, 7), EmaPrice::new(1_000_000, 3));
+    let weight = smoothing_from_period(9);
+
+    let average = geometric_price_weighted_average(prev, incoming, weight);
+    let inverted_average =
+        geometric_price_weighted_average(prev.reciprocal().unwrap(), incoming.reciprocal().unwrap(), weight);
+    assert_eq!(
+        Rational::from(average) * Rational::from(inverted_average),
+        Rational::one()
+    );
+
+    // the arithmetic average is not symmetric
+    let average = price_weighted_average(prev, incoming, weight);
+    let inverted_average = price_weighted_average(prev.reciprocal().unwrap(), incoming.reciprocal().unwrap(), weight);
+    assert!(Rational::from(average) * Rational::from(inverted_average) > Rational::one());
+}
+
+#[test]
+fn iterated_geometric_price_ema_works() {
+    let (prev, incoming) = (EmaPrice::new(1, 1), EmaPrice::new(4, 1));
+    let smoothing = fraction::frac(1, 2);
+    // 1 - (1/2)^2 = 3/4 => 4^(3/4) = 2^(3/2)
+    let expected = high_precision::precise_geometric_weighted_average(
+        Rational::from(1),
+        Rational::from(4),
+        Rational::from((3, 4)),
+    );
+    let ema = iterated_geometric_price_ema(2, prev, incoming, smoothing);
+    assert_rational_relative_approx_eq!(Rational::from(ema), expected, Rational::from((1, 1e17 as u128)));
+
+    // iterating matches stepwise updates
+    let mut stepwise = prev;
+    for _ in 0..10 {
+        stepwise = geometric_price_weighted_average(stepwise, incoming, smoothing_from_period(7));
+    }
+    let iterated = iterated_geometric_price_ema(10, prev, incoming, smoothing_from_period(7));
+    assert_rational_relative_approx_eq!(
+        Rational::from(iterated),
+        Rational::from(stepwise),
+        Rational::from((1, 1e16 as u128))
+    );
+}
+
+#[test]
+fn update_outdated_to_current_geometric_works() {
+    let outdated = (EmaPrice::new(1, 1), (100, 200, 300, 400), (1_000, 2_000));
+    let update_with = (EmaPrice::new(4, 1), (3_000, 4_000));
+    let smoothing = fraction::frac(1, 2);
+    let (price, volume, liquidity) = update_outdated_to_current_geometric(1, outdated, update_with, smoothing);
+    assert_rational_relative_approx_eq!(
+        Rational::from(price),
+        Rational::from(2),
+        Rational::from((1, 1e17 as u128))
+    );
+    assert_eq!(volume, (50, 100, 150, 200));
+    assert_eq!(liquidity, (2_000, 3_000));
+
+    let incoming = (EmaPrice::new(4, 1), (0, 0, 0, 0), (3_000, 4_000));
+    assert_eq!(
+        calculate_new_by_integrating_incoming_geometric(outdated, incoming, smoothing),
+        (price, volume, liquidity)
+    );
+}
@@ -6,7 +6,7 @@ use crate::types::Balance;
 use num_traits::{One, Pow};
 use proptest::prelude::*;
 use rug::ops::PowAssign;
-use rug::{Integer, Rational};
+use rug::{Float, Integer, Rational};
 use std::ops::{Mul, ShrAssign};
 
 /// Round the given `r` to a close number where numerator and denominator have <= 256 bits.
@@ -131,6 +131,29 @@ pub fn precise_price_ema(history: Vec<(EmaPrice, u32)>, smoothing: Rational) ->
     current
 }
 
+/// Calculate the weighted geometric average for the given values by using arbitrary precision math.
+/// Returns a `Rational` of (high but) limited precision because the logarithm is irrational.
+pub fn precise_geometric_weighted_average(prev: Rational, incoming: Rational, weight: Rational) -> Rational {
+    let precision = 512;
+    let ln_prev = Float::with_val(precision, &prev).ln();
+    let ln_incoming = Float::with_val(precision, &incoming).ln();
+    let ln_average = ln_prev.clone() + Float::with_val(precision, &weight) * (ln_incoming - ln_prev);
+    ln_average.exp().to_rational().unwrap()
+}
+
+/// Determine the geometric exponential moving average of a history of price values.
+/// Starts the EMA with the first value.
+/// Uses a `pow` approximation with 256 bit precision to reduce execution time.
+pub fn precise_geometric_price_ema(history: Vec<(EmaPrice, u32)>, smoothing: Rational) -> Rational {
+    assert!(!history.is_empty());
+    let mut current = Rational::from(history[0].0);
+    for (price, iterations) in history.into_iter().skip(1) {
+        let smoothing_adj = precise_exp_smoothing(smoothing.clone(), iterations);
+        current = precise_geometric_weighted_average(current.clone(), Rational::from(price), smoothing_adj.clone());
+    }
+    current
+}
+
 // --- Tests
 
 #[test]
@@ -571,3 +571,86 @@ proptest! {
         prop_assert!(res <= EmaPrice::new_unchecked(a, b));
     }
 }
+
+fn any_non_zero_price() -> impl Strategy<Value = EmaPrice> {
+    (1..u128::MAX, 1..u128::MAX).prop_map(|(n, d)| EmaPrice::new_unchecked(n, d))
+}
+
+fn ema_non_zero_price_history() -> impl Strategy<Value = Vec<(EmaPrice, u32)>> {
+    prop::collection::vec((any_non_zero_price(), iterations()), 2..50)
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(1_000))]
+    #[test]
+    fn geometric_price_weighted_average_precision(
+        (prev, incoming) in (any_non_zero_price(), any_non_zero_price()),
+        weight in fraction_above_zero_and_less_or_equal_one(),
+    ) {
+        let average = geometric_price_weighted_average(prev, incoming, weight);
+        let expected = high_precision::precise_geometric_weighted_average(
+            prev.into(),
+            incoming.into(),
+            fraction_to_high_precision(weight),
+        );
+        let relative_tolerance = Rational::from((1, 1e16 as u128));
+        prop_assert_rational_relative_approx_eq!(
+            Rational::from(average),
+            expected,
+            relative_tolerance,
+            "high precision should be equal to low precision within tolerance"
+        );
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(1_000))]
+    #[test]
+    fn geometric_price_weighted_average_is_reciprocal_symmetric(
+        (prev, incoming) in (any_non_zero_price(), any_non_zero_price()),
+        weight in fraction_above_zero_and_less_or_equal_one(),
+    ) {
+        let average = geometric_price_weighted_average(prev, incoming, weight);
+        let inverted_average = geometric_price_weighted_average(
+            prev.reciprocal().unwrap(),
+            incoming.reciprocal().unwrap(),
+            weight,
+        );
+        let product = Rational::from(average) * Rational::from(inverted_average);
+        let tolerance = Rational::from((1, 1e30 as u128));
+        prop_assert_rational_approx_eq!(product, Rational::one(), tolerance);
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(100))]
+    #[test]
+    fn geometric_ema_price_history_precision(
+        history in ema_non_zero_price_history(),
+        period in typical_period(),
+    ) {
+        let smoothing = smoothing_from_period(period);
+        let rug_ema = high_precision::precise_geometric_price_ema(history.clone(), fraction_to_high_precision(smoothing));
+
+        let mut ema = history[0].0;
+        let mut inverted_ema = history[0].0.reciprocal().unwrap();
+        for (price, iterations) in history.into_iter().skip(1) {
+            ema = iterated_geometric_price_ema(iterations, ema, price, smoothing);
+            inverted_ema = iterated_geometric_price_ema(iterations, inverted_ema, price.reciprocal().unwrap(), smoothing);
+        }
+
+        let relative_tolerance = Rational::from((1, 1e14 as u128));
+        prop_assert_rational_relative_approx_eq!(
+            Rational::from(ema),
+            rug_ema.clone(),
+            relative_tolerance.clone(),
+            "high precision should be equal to low precision within tolerance"
+        );
+        prop_assert_rational_relative_approx_eq!(
+            Rational::from(inverted_ema),
+            Rational::one() / rug_ema,
+            relative_tolerance,
+            "inverted EMA should be the reciprocal of the EMA within tolerance"
+        );
+    }
+}
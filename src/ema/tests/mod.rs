@@ -7,9 +7,15 @@ use crate::test_utils::{assert_approx_eq, assert_rational_approx_eq, assert_rati
 use crate::transcendental::saturating_powi_high_precision;
 use crate::types::Fraction;
 
+mod aggregate;
+mod composition;
+mod continuous;
+mod geometric;
 mod high_precision;
 mod invariants;
 mod test_data;
+mod valuation;
+mod volatility;
 use test_data::*;
 
 use num_traits::One;
@@ -0,0 +1,108 @@
+use crate::ema::*;
+use crate::test_utils::{prop_assert_rational_relative_approx_eq, MAX_BALANCE, MIN_BALANCE};
+use crate::types::Balance;
+
+use proptest::prelude::*;
+use rug::Rational;
+
+const ONE: Balance = 1_000_000_000_000;
+
+#[test]
+fn chain_prices_should_work() {
+    assert_eq!(chain_prices(&[]), Some(EmaPrice::one()));
+    assert_eq!(chain_prices(&[EmaPrice::new(3, 2)]), Some(EmaPrice::new(3, 2)));
+    let chained = chain_prices(&[EmaPrice::new(3, 2), EmaPrice::new(4, 9)]).unwrap();
+    assert_eq!(Rational::from(chained), Rational::from((2, 3)));
+    // zero price propagates
+    let chained = chain_prices(&[EmaPrice::new(3, 2), EmaPrice::zero(), EmaPrice::new(4, 9)]).unwrap();
+    assert!(chained.is_zero());
+    // not representable
+    assert_eq!(chain_prices(&[EmaPrice::new(u128::MAX, 1), EmaPrice::new(2, 1)]), None);
+}
+
+#[test]
+fn volume_in_reference_should_work() {
+    let volume = (10 * ONE, 20 * ONE, 4 * ONE, 8 * ONE);
+    let (price_a, price_b) = (EmaPrice::new(2, 1), EmaPrice::new(1, 1));
+    assert_eq!(
+        volume_in_reference(volume, price_a, price_b),
+        Some((20 * ONE, 20 * ONE, 8 * ONE, 8 * ONE))
+    );
+    assert_eq!(total_volume_in_reference(volume, price_a, price_b), Some(28 * ONE));
+    // rounds down
+    assert_eq!(
+        volume_in_reference((1, 1, 1, 1), EmaPrice::new(1, 3), EmaPrice::new(2, 3)),
+        Some((0, 0, 0, 0))
+    );
+    assert_eq!(total_volume_in_reference((1, 0, 0, 0), price_a, price_b), Some(1));
+    assert_eq!(total_volume_in_reference((1, 0, 0, 0), price_b, price_b), Some(0));
+    // sum of the legs would overflow, but the average does not
+    let max_volume = (u128::MAX, u128::MAX, 0, 0);
+    assert_eq!(total_volume_in_reference(max_volume, price_b, price_b), Some(u128::MAX));
+    assert_eq!(volume_in_reference(max_volume, price_a, price_b), None);
+}
+
+#[test]
+fn liquidity_in_reference_should_work() {
+    let liquidity = (100 * ONE, 50 * ONE);
+    assert_eq!(
+        liquidity_in_reference(liquidity, EmaPrice::new(1, 2), EmaPrice::new(3, 1)),
+        Some(200 * ONE)
+    );
+    assert_eq!(
+        liquidity_in_reference((u128::MAX, 1), EmaPrice::one(), EmaPrice::one()),
+        None
+    );
+}
+
+#[test]
+fn oracle_value_in_reference_should_work() {
+    // 1 A = 2 B and 1 B = 0.5 USD
+    let price = EmaPrice::new(2, 1);
+    let b_in_usd = EmaPrice::new(1, 2);
+    let volume = (10 * ONE, 20 * ONE, 4 * ONE, 8 * ONE);
+    let liquidity = (100 * ONE, 200 * ONE);
+    assert_eq!(
+        oracle_value_in_reference(price, volume, liquidity, b_in_usd),
+        Some((14 * ONE, 200 * ONE))
+    );
+}
+
+fn price() -> impl Strategy<Value = EmaPrice> {
+    (1..u64::MAX as u128, 1..u64::MAX as u128).prop_map(|(n, d)| EmaPrice::new(n, d))
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(1_000))]
+    #[test]
+    fn chained_price_precision_is_bounded(prices in prop::collection::vec(price(), 1..8)) {
+        let exact = prices.iter().fold(Rational::from(1), |acc, p| acc * Rational::from(*p));
+        // skip paths whose intermediate values are out of the precise range
+        let mut intermediate = Rational::from(1);
+        for p in prices.iter() {
+            intermediate *= Rational::from(*p);
+            prop_assume!(intermediate >= Rational::from((1, 1_u128 << 60)));
+            prop_assume!(intermediate <= Rational::from(u128::MAX));
+        }
+        let chained = chain_prices(&prices).unwrap();
+        let tolerance = Rational::from((prices.len() as u128, 1_u128 << 62));
+        prop_assert_rational_relative_approx_eq!(Rational::from(chained), exact, tolerance);
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(1_000))]
+    #[test]
+    fn reference_liquidity_is_sum_of_values(
+        a in MIN_BALANCE..MAX_BALANCE,
+        b in MIN_BALANCE..MAX_BALANCE,
+        (price_a, price_b) in (price(), price()),
+    ) {
+        prop_assume!(Rational::from(price_a) <= 1_000_000);
+        prop_assume!(Rational::from(price_b) <= 1_000_000);
+        let value = liquidity_in_reference((a, b), price_a, price_b).unwrap();
+        let exact = Rational::from(a) * Rational::from(price_a) + Rational::from(b) * Rational::from(price_b);
+        prop_assert!(Rational::from(value) <= exact);
+        prop_assert!(exact - Rational::from(value) < 2);
+    }
+}
@@ -0,0 +1,155 @@
+use crate::ema::*;
+use crate::fraction;
+use crate::test_utils::{assert_rational_relative_approx_eq, prop_assert_rational_relative_approx_eq};
+use crate::types::Fraction;
+
+use num_traits::One;
+use proptest::prelude::*;
+use rug::Rational;
+
+#[test]
+fn price_deviation_weighted_average_should_work() {
+    let smoothing = fraction::frac(1, 2);
+    let deviation = price_deviation_weighted_average(
+        EmaDeviation::zero(),
+        EmaPrice::new(1, 1),
+        EmaPrice::new(3, 1),
+        smoothing,
+    );
+    assert_eq!(Rational::from(deviation), Rational::from(1));
+    // deviation is symmetric
+    let deviation = price_deviation_weighted_average(
+        EmaDeviation::zero(),
+        EmaPrice::new(3, 1),
+        EmaPrice::new(1, 1),
+        smoothing,
+    );
+    assert_eq!(Rational::from(deviation), Rational::from(1));
+    // no deviation lets the previous deviation decay
+    let deviation = price_deviation_weighted_average(
+        EmaDeviation::new(4, 1),
+        EmaPrice::new(3, 1),
+        EmaPrice::new(3, 1),
+        smoothing,
+    );
+    assert_eq!(Rational::from(deviation), Rational::from(2));
+}
+
+#[test]
+fn iterated_price_deviation_ema_should_work() {
+    let smoothing = fraction::frac(1, 2);
+    let (prev_deviation, prev_price, incoming_price) =
+        (EmaDeviation::new(1, 1), EmaPrice::new(1, 1), EmaPrice::new(3, 1));
+    assert_eq!(
+        iterated_price_deviation_ema(0, prev_deviation, prev_price, incoming_price, smoothing),
+        prev_deviation
+    );
+    // 1/2 * 1 + 1 * 1/2 * 2
+    let deviation = iterated_price_deviation_ema(1, prev_deviation, prev_price, incoming_price, smoothing);
+    assert_eq!(Rational::from(deviation), Rational::from((3, 2)));
+    // 1/4 * 1 + 2 * 1/2 * 1/2 * 2
+    let deviation = iterated_price_deviation_ema(2, prev_deviation, prev_price, incoming_price, smoothing);
+    assert_eq!(Rational::from(deviation), Rational::from((5, 4)));
+    // smoothing of one only considers the last deviation
+    let deviation = iterated_price_deviation_ema(5, prev_deviation, prev_price, incoming_price, Fraction::one());
+    assert_eq!(Rational::from(deviation), Rational::from(0));
+    let deviation = iterated_price_deviation_ema(1, prev_deviation, prev_price, incoming_price, Fraction::one());
+    assert_eq!(Rational::from(deviation), Rational::from(2));
+}
+
+#[test]
+fn iterated_price_deviation_ema_should_match_stepwise_updates() {
+    let smoothing = smoothing_from_period(100);
+    let (mut deviation, mut price) = (EmaDeviation::new(1, 100), EmaPrice::new(1, 1));
+    let incoming_price = EmaPrice::new(5, 4);
+    let iterations = 200;
+    for _ in 0..iterations {
+        deviation = price_deviation_weighted_average(deviation, price, incoming_price, smoothing);
+        price = price_weighted_average(price, incoming_price, smoothing);
+    }
+    let iterated = iterated_price_deviation_ema(
+        iterations,
+        EmaDeviation::new(1, 100),
+        EmaPrice::new(1, 1),
+        incoming_price,
+        smoothing,
+    );
+    assert_rational_relative_approx_eq!(
+        Rational::from(iterated),
+        Rational::from(deviation),
+        Rational::from((1, 1_000_000_000_000_000_u128))
+    );
+}
+
+#[test]
+fn confidence_band_should_work() {
+    let (lower, upper) = confidence_band(EmaPrice::new(10, 1), EmaDeviation::new(1, 1), EmaPrice::new(2, 1));
+    assert_eq!(Rational::from(lower), Rational::from(8));
+    assert_eq!(Rational::from(upper), Rational::from(12));
+    // lower bound saturates at zero
+    let (lower, upper) = confidence_band(EmaPrice::new(1, 1), EmaDeviation::new(3, 1), EmaPrice::one());
+    assert!(lower.is_zero());
+    assert_eq!(Rational::from(upper), Rational::from(4));
+    // no deviation collapses the band
+    let (lower, upper) = confidence_band(EmaPrice::new(7, 3), EmaDeviation::zero(), EmaPrice::new(2, 1));
+    assert_eq!((lower, upper), (EmaPrice::new(7, 3), EmaPrice::new(7, 3)));
+}
+
+#[test]
+fn relative_deviation_should_work() {
+    let relative = relative_deviation(EmaPrice::new(10, 1), EmaDeviation::new(1, 1));
+    assert_eq!(Rational::from(relative), Rational::from((1, 10)));
+    assert_eq!(
+        relative_deviation(EmaPrice::zero(), EmaDeviation::new(1, 1)),
+        EmaPrice::max_value()
+    );
+    assert!(relative_deviation(EmaPrice::zero(), EmaDeviation::zero()).is_zero());
+}
+
+fn price() -> impl Strategy<Value = EmaPrice> {
+    (1..u64::MAX as u128, 1..u64::MAX as u128).prop_map(|(n, d)| EmaPrice::new(n, d))
+}
+
+/// Prices within `[1e-6, 1e6]` so that rounding of the price does not dominate the deviation.
+fn realistic_price() -> impl Strategy<Value = EmaPrice> {
+    (1..1_000_000_u128, 1..1_000_000_u128).prop_map(|(n, d)| EmaPrice::new(n, d))
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(500))]
+    #[test]
+    fn iterated_deviation_matches_stepwise_updates(
+        (prev_deviation, prev_price, incoming_price) in (realistic_price(), realistic_price(), realistic_price()),
+        period in 10_u64..1_000,
+        iterations in 1_u32..20,
+    ) {
+        let smoothing = smoothing_from_period(period);
+        let (mut deviation, mut price) = (prev_deviation, prev_price);
+        for _ in 0..iterations {
+            deviation = price_deviation_weighted_average(deviation, price, incoming_price, smoothing);
+            price = price_weighted_average(price, incoming_price, smoothing);
+        }
+        let iterated = iterated_price_deviation_ema(iterations, prev_deviation, prev_price, incoming_price, smoothing);
+        prop_assert_rational_relative_approx_eq!(
+            Rational::from(iterated),
+            Rational::from(deviation),
+            Rational::from((1, 1_000_000_000_000_u128))
+        );
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(500))]
+    #[test]
+    fn confidence_band_contains_price(
+        (price, deviation, multiplier) in (price(), price(), price()),
+    ) {
+        let (lower, upper) = confidence_band(price, deviation, multiplier);
+        prop_assert!(lower <= price);
+        prop_assert!(upper >= price);
+        let width = Rational::from(deviation) * Rational::from(multiplier);
+        let exact_lower = (Rational::from(price) - width.clone()).max(Rational::from(0));
+        prop_assert!(Rational::from(lower) <= exact_lower);
+        prop_assert!(Rational::from(upper) >= Rational::from(price) + width);
+    }
+}
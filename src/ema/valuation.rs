@@ -0,0 +1,78 @@
+use super::math::{EmaLiquidity, EmaPrice, EmaVolume};
+use crate::support::rational::Rounding;
+use crate::types::Balance;
+
+/// Chain the given `prices` into a single price by multiplying them.
+///
+/// E.g. chaining the price of `A` in `LRNA` with the price of `LRNA` in `USD` results in the
+/// price of `A` in `USD`. An empty path results in a price of `1`.
+///
+/// Each multiplication is rounded to the nearest representable `EmaPrice` which loses at most
+/// `2^-62` of relative precision for prices within `[2^-60, u128::MAX]`.
+/// Returns `None` if an intermediate price is not representable.
+pub fn chain_prices(prices: &[EmaPrice]) -> Option<EmaPrice> {
+    prices
+        .iter()
+        .try_fold(EmaPrice::one(), |acc, price| acc.checked_mul(price, Rounding::Nearest))
+}
+
+/// Convert `volume` into reference currency units.
+/// `price_a` and `price_b` are the prices of asset a and asset b in the reference currency.
+///
+/// Note: Rounds down.
+pub fn volume_in_reference(volume: EmaVolume, price_a: EmaPrice, price_b: EmaPrice) -> Option<EmaVolume> {
+    let (a_in, b_out, a_out, b_in) = volume;
+    Some((
+        price_a.mul_balance(a_in, Rounding::Down)?,
+        price_b.mul_balance(b_out, Rounding::Down)?,
+        price_a.mul_balance(a_out, Rounding::Down)?,
+        price_b.mul_balance(b_in, Rounding::Down)?,
+    ))
+}
+
+/// Calculate the total traded value of `volume` in reference currency units.
+/// `price_a` and `price_b` are the prices of asset a and asset b in the reference currency.
+///
+/// Both legs of a trade are averaged, so every trade is only counted once:
+/// `((a_in + b_out) + (a_out + b_in)) / 2`.
+///
+/// Note: Rounds down.
+pub fn total_volume_in_reference(volume: EmaVolume, price_a: EmaPrice, price_b: EmaPrice) -> Option<Balance> {
+    let (a_in, b_out, a_out, b_in) = volume_in_reference(volume, price_a, price_b)?;
+    // halve every value first to not overflow the sum
+    let halves = (a_in / 2)
+        .checked_add(b_out / 2)?
+        .checked_add(a_out / 2)?
+        .checked_add(b_in / 2)?;
+    let remainders = (a_in % 2 + b_out % 2 + a_out % 2 + b_in % 2) / 2;
+    halves.checked_add(remainders)
+}
+
+/// Calculate the value of `liquidity` in reference currency units.
+/// `price_a` and `price_b` are the prices of asset a and asset b in the reference currency.
+///
+/// Note: Rounds down.
+pub fn liquidity_in_reference(liquidity: EmaLiquidity, price_a: EmaPrice, price_b: EmaPrice) -> Option<Balance> {
+    let (a, b) = liquidity;
+    price_a
+        .mul_balance(a, Rounding::Down)?
+        .checked_add(price_b.mul_balance(b, Rounding::Down)?)
+}
+
+/// Value the volume and liquidity of an oracle entry of the pair `(a, b)` in the reference currency.
+///
+/// `price` is the EMA price of asset a denominated in asset b and `b_in_reference` is the price of
+/// asset b in the reference currency, e.g. the chained price returned by `chain_prices`.
+///
+/// Returns `(total_volume, liquidity)` in reference currency units.
+pub fn oracle_value_in_reference(
+    price: EmaPrice,
+    volume: EmaVolume,
+    liquidity: EmaLiquidity,
+    b_in_reference: EmaPrice,
+) -> Option<(Balance, Balance)> {
+    let a_in_reference = chain_prices(&[price, b_in_reference])?;
+    let total_volume = total_volume_in_reference(volume, a_in_reference, b_in_reference)?;
+    let liquidity = liquidity_in_reference(liquidity, a_in_reference, b_in_reference)?;
+    Some((total_volume, liquidity))
+}
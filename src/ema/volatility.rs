@@ -0,0 +1,100 @@
+use super::math::{multiply, price_weighted_average, round_to_rational, rounding_add, saturating_sub, EmaPrice};
+use crate::support::rational::Rounding;
+use crate::transcendental::saturating_powi_high_precision;
+use crate::types::Fraction;
+
+use num_traits::{One, Zero};
+use primitive_types::{U256, U512};
+
+/// EmaDeviation is the exponentially weighted mean absolute deviation of an `EmaPrice`.
+pub type EmaDeviation = EmaPrice;
+
+/// Calculate the new mean absolute deviation by integrating the deviation of `incoming_price`
+/// from `prev_price` with the `prev_deviation`.
+/// `prev_price` is the oracle price before integrating `incoming_price`.
+/// `smoothing` is the smoothing factor of the EMA, i.e. the same as used for the price.
+///
+/// Note: Rounding is biased towards `prev_deviation`.
+pub fn price_deviation_weighted_average(
+    prev_deviation: EmaDeviation,
+    prev_price: EmaPrice,
+    incoming_price: EmaPrice,
+    smoothing: Fraction,
+) -> EmaDeviation {
+    let deviation = round_to_rational(to_u512(abs_diff(prev_price, incoming_price)), Rounding::Nearest);
+    price_weighted_average(prev_deviation, deviation, smoothing)
+}
+
+/// Calculate the iterated mean absolute deviation for the given prices.
+/// `iterations` is the number of iterations the `incoming_price` is integrated.
+/// `prev_deviation` and `prev_price` are the previous oracle values.
+/// `smoothing` is the smoothing factor of the EMA, i.e. the same as used for the price.
+///
+/// With `c = 1 - smoothing` the price deviates by `|prev_price - incoming_price| * c^k` in the
+/// `k`-th iteration, so the deviation after `n` iterations is given by
+/// `c^n * prev_deviation + n * smoothing * c^(n-1) * |prev_price - incoming_price|`.
+pub fn iterated_price_deviation_ema(
+    iterations: u32,
+    prev_deviation: EmaDeviation,
+    prev_price: EmaPrice,
+    incoming_price: EmaPrice,
+    smoothing: Fraction,
+) -> EmaDeviation {
+    debug_assert!(smoothing <= Fraction::one());
+    if iterations.is_zero() {
+        return prev_deviation;
+    }
+    let complement = Fraction::one() - smoothing;
+    let exp_complement: Fraction = saturating_powi_high_precision(complement, iterations);
+    // `saturating_powi_high_precision` returns zero for `0^0`
+    let prev_exp_complement: Fraction = if iterations == 1 {
+        Fraction::one()
+    } else {
+        saturating_powi_high_precision(complement, iterations - 1)
+    };
+    // `n * smoothing * c^(n-1) = n * (c^(n-1) - c^n)` is a binomial probability so it is <= 1
+    let deviation_weight = prev_exp_complement
+        .saturating_sub(exp_complement)
+        .saturating_mul_int(u128::from(iterations))
+        .min(Fraction::one());
+
+    let remaining = multiply(exp_complement, (prev_deviation.n.into(), prev_deviation.d.into()));
+    let added = multiply(deviation_weight, abs_diff(prev_price, incoming_price));
+    let remaining = round_to_rational(remaining, Rounding::Down);
+    rounding_add(remaining, added, Rounding::Down)
+}
+
+/// Calculate the confidence band `[price - width, price + width]` with
+/// `width = multiplier * deviation`.
+///
+/// Note: Rounds outwards, so the band always contains the exact band.
+pub fn confidence_band(price: EmaPrice, deviation: EmaDeviation, multiplier: EmaPrice) -> (EmaPrice, EmaPrice) {
+    let width = deviation.saturating_mul(&multiplier, Rounding::Up);
+    let lower = price.saturating_sub(&width, Rounding::Down);
+    let upper = price.saturating_add(&width, Rounding::Up);
+    (lower, upper)
+}
+
+/// Calculate the deviation relative to the price: `deviation / price`.
+/// Can be used to scale fees with the observed volatility.
+///
+/// Note: Rounds up and saturates at the largest representable value (e.g. for a zero price).
+pub fn relative_deviation(price: EmaPrice, deviation: EmaDeviation) -> EmaPrice {
+    if deviation.is_zero() {
+        return EmaPrice::zero();
+    }
+    deviation.saturating_div(&price, Rounding::Up)
+}
+
+/// Calculate `|l - r|` as a tuple of `U256` for full precision.
+fn abs_diff(l: EmaPrice, r: EmaPrice) -> (U256, U256) {
+    if l >= r {
+        saturating_sub(l, r)
+    } else {
+        saturating_sub(r, l)
+    }
+}
+
+fn to_u512((n, d): (U256, U256)) -> (U512, U512) {
+    (n.into(), d.into())
+}
@@ -1,5 +1,8 @@
 use crate::types::Balance;
+use crate::{ensure, to_balance, to_u256, MathError, MathError::InvalidFee, MathError::Overflow};
+use core::convert::TryFrom;
 use num_traits::Zero;
+use primitive_types::U256;
 
 pub fn calculate_pool_trade_fee(amount: Balance, fee: (u32, u32)) -> Option<Balance> {
     let numerator = fee.0;
@@ -18,6 +21,32 @@ pub fn calculate_pool_trade_fee(amount: Balance, fee: (u32, u32)) -> Option<Bala
         .checked_mul(numerator as Balance)
 }
 
+/// Calculating fee amount given the amount and fee as (numerator, denominator).
+/// Formula : AMOUNT * FEE_NUMERATOR / FEE_DENOMINATOR
+///
+/// The result is rounded up so that the fee always favours the pool.
+/// Zero numerator or denominator means no fee. Fee greater than 100% is not allowed.
+pub fn calculate_fee_amount_rounded_up(amount: Balance, fee: (u32, u32)) -> Result<Balance, MathError> {
+    let (numerator, denominator) = fee;
+
+    if numerator.is_zero() || denominator.is_zero() {
+        return Ok(Balance::zero());
+    }
+
+    ensure!(numerator <= denominator, InvalidFee);
+
+    let (amount_hp, numerator_hp, denominator_hp) = to_u256!(amount, numerator, denominator);
+
+    // Bare math is safe because numerator and denominator fit into u32
+    let fee_amount_hp = amount_hp
+        .checked_mul(numerator_hp)
+        .and_then(|v| v.checked_add(denominator_hp - U256::one()))
+        .and_then(|v| v.checked_div(denominator_hp))
+        .ok_or(Overflow)?;
+
+    to_balance!(fee_amount_hp)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -96,4 +125,20 @@ mod tests {
         assert_eq!(calculate_pool_trade_fee(max_amount, (1, 0)), Some(0));
         assert_eq!(calculate_pool_trade_fee(1_000, (0, 1)), Some(0));
     }
+
+    #[test]
+    fn fee_amount_rounded_up_should_work() {
+        assert_eq!(calculate_fee_amount_rounded_up(1_000, (2, 1_000)), Ok(2));
+        assert_eq!(calculate_fee_amount_rounded_up(1_001, (2, 1_000)), Ok(3));
+        assert_eq!(calculate_fee_amount_rounded_up(0, (2, 1_000)), Ok(0));
+        assert_eq!(calculate_fee_amount_rounded_up(1_000, (0, 1_000)), Ok(0));
+        assert_eq!(calculate_fee_amount_rounded_up(1_000, (2, 0)), Ok(0));
+        assert_eq!(calculate_fee_amount_rounded_up(Balance::MAX, (1, 1)), Ok(Balance::MAX));
+        assert_eq!(
+            calculate_fee_amount_rounded_up(Balance::MAX, (1, u32::MAX)),
+            Ok(79228162532711081671548469249)
+        );
+        assert_eq!(calculate_fee_amount_rounded_up(1_000, (11, 10)), Err(InvalidFee));
+        assert_eq!(calculate_fee_amount_rounded_up(0, (u32::MAX, 1)), Err(InvalidFee));
+    }
 }
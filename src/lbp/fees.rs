@@ -0,0 +1,63 @@
+use crate::fee::calculate_fee_amount_rounded_up;
+use crate::types::Balance;
+use crate::{to_balance, to_u256, MathError, MathError::Overflow};
+use core::convert::TryFrom;
+use num_traits::Zero;
+use primitive_types::U256;
+
+/// Calculating fees of a trade in a pool which charges a different fee until a repay target is reached.
+///
+/// Fees charged at `pre_target_fee` rate are used to repay the target. Once `collected` reaches
+/// `repay_target`, fees are charged at `post_target_fee` rate and go to the pool owner.
+/// If the trade crosses the target, the part of `amount` needed to reach the target is charged
+/// at the pre-target rate and the rest at the post-target rate.
+///
+/// - `amount` - trade amount the fee is charged from
+/// - `collected` - total fee collected towards the repay target so far
+/// - `repay_target` - total fee to be collected before switching to the post-target rate
+/// - `pre_target_fee` - fee rate charged until the target is reached
+/// - `post_target_fee` - fee rate charged after the target is reached
+///
+/// Returns `(amount_after_fee, fee_to_owner, fee_to_repay)`.
+pub fn calculate_fees(
+    amount: Balance,
+    collected: Balance,
+    repay_target: Balance,
+    pre_target_fee: (u32, u32),
+    post_target_fee: (u32, u32),
+) -> Result<(Balance, Balance, Balance), MathError> {
+    let remaining_target = repay_target.saturating_sub(collected);
+
+    let (fee_to_owner, fee_to_repay) = if remaining_target.is_zero() {
+        (calculate_fee_amount_rounded_up(amount, post_target_fee)?, 0)
+    } else {
+        let fee = calculate_fee_amount_rounded_up(amount, pre_target_fee)?;
+        if fee <= remaining_target {
+            (0, fee)
+        } else {
+            // Trade crosses the target, so it is split into the part which reaches the target and the rest.
+            // We round the first part up, so the rest is never charged more than necessary.
+            let (remaining_hp, n, d) = to_u256!(remaining_target, pre_target_fee.0, pre_target_fee.1);
+            let pre_target_amount = remaining_hp
+                .checked_mul(d)
+                .and_then(|v| v.checked_add(n.checked_sub(U256::one())?))
+                .and_then(|v| v.checked_div(n))
+                .ok_or(Overflow)?;
+            let pre_target_amount = to_balance!(pre_target_amount)?;
+
+            let post_target_amount = amount.checked_sub(pre_target_amount).ok_or(Overflow)?;
+
+            (
+                calculate_fee_amount_rounded_up(post_target_amount, post_target_fee)?,
+                remaining_target,
+            )
+        }
+    };
+
+    let amount_after_fee = amount
+        .checked_sub(fee_to_owner)
+        .and_then(|v| v.checked_sub(fee_to_repay))
+        .ok_or(Overflow)?;
+
+    Ok((amount_after_fee, fee_to_owner, fee_to_repay))
+}
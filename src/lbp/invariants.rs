@@ -52,3 +52,107 @@ proptest! {
         assert_approx_eq!(a1*a2, b1*b2, max_delta, "The invariant does not hold")
     }
 }
+
+fn exponential_rate() -> impl Strategy<Value = u32> {
+    0..10u32
+}
+
+fn steps() -> impl Strategy<Value = u32> {
+    1..1_000u32
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(1000))]
+    #[test]
+    fn non_linear_weights_hit_endpoints_and_are_monotonic(
+        start_x_block in start_blocks(),
+        lbp_length in lbp_length(),
+        start_y_weight in initial_weight(),
+        end_y_weight in final_weight(),
+        rate in exponential_rate(),
+        steps in steps(),
+    ) {
+        let end_x_block = start_x_block.checked_add(lbp_length).unwrap();
+        let at_block = rand::thread_rng().gen_range(start_x_block..end_x_block);
+        let middle_block = start_x_block + lbp_length / 2;
+
+        let schedules = vec![
+            crate::lbp::WeightSchedule::Exponential {
+                start_x: start_x_block,
+                end_x: end_x_block,
+                start_y: start_y_weight,
+                end_y: end_y_weight,
+                rate: crate::types::FixedBalance::from_num(rate),
+            },
+            crate::lbp::WeightSchedule::Stepwise {
+                start_x: start_x_block,
+                end_x: end_x_block,
+                start_y: start_y_weight,
+                end_y: end_y_weight,
+                steps,
+            },
+            crate::lbp::WeightSchedule::PiecewiseLinear(vec![
+                (start_x_block, start_y_weight),
+                (middle_block, (start_y_weight + end_y_weight) / 2),
+                (end_x_block, end_y_weight),
+            ]),
+        ];
+
+        for schedule in schedules {
+            assert_eq!(lbp::calculate_weights(&schedule, start_x_block), Ok(start_y_weight));
+            assert_eq!(lbp::calculate_weights(&schedule, end_x_block), Ok(end_y_weight));
+
+            let weight = lbp::calculate_weights(&schedule, at_block).unwrap();
+            let next_weight = lbp::calculate_weights(&schedule, at_block + 1).unwrap();
+            assert!(start_y_weight <= weight && weight <= next_weight && next_weight <= end_y_weight);
+        }
+    }
+}
+
+fn reserve() -> impl Strategy<Value = u128> {
+    1_000_000_000_000u128..1_000_000_000_000_000_000_000u128
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(1000))]
+    #[test]
+    fn spot_price_ratio_agrees_with_scaled_spot_price(
+        in_reserve in reserve(),
+        out_reserve in reserve(),
+        in_weight in initial_weight(),
+        out_weight in final_weight(),
+        amount in reserve(),
+    ) {
+        let ratio = lbp::spot_price_ratio(in_reserve, out_reserve, in_weight, out_weight).unwrap();
+        let spot_price = lbp::calculate_spot_price(in_reserve, out_reserve, in_weight, out_weight, amount).unwrap();
+
+        let scaled = U256::from(amount) * U256::from(ratio.n) / U256::from(ratio.d);
+
+        assert_approx_eq!(scaled, U256::from(spot_price), U256::one(), "Spot prices do not match")
+    }
+}
+
+fn fee() -> impl Strategy<Value = (u32, u32)> {
+    (0u32..1000u32).prop_map(|n| (n, 1000u32))
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(10000))]
+    #[test]
+    fn fees_never_exceed_amount_or_target(
+        amount in 0..1_000_000_000_000_000u128,
+        collected in 0..1_000_000_000_000u128,
+        repay_target in 0..1_000_000_000_000u128,
+        pre_target_fee in fee(),
+        post_target_fee in fee(),
+    ) {
+        let (amount_after_fee, fee_to_owner, fee_to_repay) =
+            crate::lbp::fees::calculate_fees(amount, collected, repay_target, pre_target_fee, post_target_fee).unwrap();
+
+        assert_eq!(amount_after_fee + fee_to_owner + fee_to_repay, amount);
+        assert!(fee_to_repay <= repay_target.saturating_sub(collected));
+        if collected >= repay_target {
+            assert_eq!(fee_to_repay, 0);
+        }
+    }
+}
@@ -3,13 +3,15 @@ use primitive_types::U256;
 
 use crate::{
     ensure, to_balance, to_lbp_weight, to_u256, MathError,
-    MathError::{Overflow, ZeroDuration, ZeroReserve, ZeroWeight},
+    MathError::{InsufficientOutReserve, OutOfRange, Overflow, ZeroDuration, ZeroReserve, ZeroWeight},
 };
 
 use core::convert::From;
 use num_traits::Zero;
+use sp_std::vec::Vec;
 
-use crate::types::{Balance, FixedBalance, LBPWeight, HYDRA_ONE};
+use crate::support::rational::{round_to_rational, Rounding};
+use crate::types::{Balance, FixedBalance, LBPWeight, Ratio, HYDRA_ONE};
 
 /// Calculating spot price given reserve of selling asset and reserve of buying asset.
 /// Formula : BUY_RESERVE * AMOUNT / SELL_RESERVE
@@ -49,7 +51,43 @@ pub fn calculate_spot_price(
     to_balance!(spot_price)
 }
 
-fn convert_to_fixed(value: Balance) -> FixedBalance {
+/// Calculating spot price given reserve of selling asset and reserve of buying asset as a rational number.
+/// Formula : BUY_RESERVE * SELL_WEIGHT / (SELL_RESERVE * BUY_WEIGHT)
+///
+/// Unlike `calculate_spot_price`, the price is not scaled by an amount and so does not lose precision
+/// for small amounts. Numerator and denominator are rounded to the nearest `u128` values if needed.
+///
+/// - `in_reserve` - reserve amount of selling asset
+/// - `out_reserve` - reserve amount of buying asset
+/// - `in_weight` - pool weight of selling asset
+/// - `out_weight` - pool weight of buying asset
+///
+/// Returns MathError in case of error
+pub fn spot_price_ratio(
+    in_reserve: Balance,
+    out_reserve: Balance,
+    in_weight: LBPWeight,
+    out_weight: LBPWeight,
+) -> Result<Ratio, MathError> {
+    ensure!(in_reserve != 0, ZeroReserve);
+    ensure!(out_weight != 0, ZeroWeight);
+
+    if out_reserve == 0 || in_weight == 0 {
+        return Ok(Ratio::zero());
+    }
+
+    let (out_reserve, in_reserve, out_weight, in_weight) = to_u256!(out_reserve, in_reserve, out_weight, in_weight);
+
+    // Both products fit into U256 as reserves are u128 and weights are u32
+    let n = out_reserve.checked_mul(in_weight).ok_or(Overflow)?;
+    let d = in_reserve.checked_mul(out_weight).ok_or(Overflow)?;
+
+    let (n, d) = round_to_rational((n, d), Rounding::Nearest);
+
+    Ok(Ratio::new(n, d))
+}
+
+pub(crate) fn convert_to_fixed(value: Balance) -> FixedBalance {
     if value == Balance::from(1u32) {
         return FixedBalance::from_num(1);
     }
@@ -60,7 +98,7 @@ fn convert_to_fixed(value: Balance) -> FixedBalance {
     FixedBalance::from_num(f) + (FixedBalance::from_num(r) / HYDRA_ONE)
 }
 
-fn convert_from_fixed(value: FixedBalance) -> Option<Balance> {
+pub(crate) fn convert_from_fixed(value: FixedBalance) -> Option<Balance> {
     let w: Balance = value.int().to_num();
     let frac = value.frac();
     let frac: Balance = frac.checked_mul_int(HYDRA_ONE)?.int().to_num();
@@ -68,7 +106,7 @@ fn convert_from_fixed(value: FixedBalance) -> Option<Balance> {
     Some(r)
 }
 
-fn round_up_fixed(value: FixedBalance) -> Result<FixedBalance, MathError> {
+pub(crate) fn round_up_fixed(value: FixedBalance) -> Result<FixedBalance, MathError> {
     let prec = FixedBalance::from_num(0.00000000001);
     value.checked_add(prec).ok_or(Overflow)
 }
@@ -224,3 +262,422 @@ pub fn calculate_linear_weights<BlockNumber: num_traits::CheckedSub + TryInto<u3
 
     to_lbp_weight!(result)
 }
+
+/// Shape of the weight curve over an interval.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WeightSchedule<BlockNumber> {
+    /// Linear interpolation between `start_y` at `start_x` and `end_y` at `end_x`.
+    Linear {
+        start_x: BlockNumber,
+        end_x: BlockNumber,
+        start_y: LBPWeight,
+        end_y: LBPWeight,
+    },
+    /// Exponential approach from `start_y` at `start_x` to `end_y` at `end_x`.
+    /// Weight changes fast at the beginning and slows down towards the end, the higher the `rate`
+    /// the more. Zero `rate` is equivalent to `Linear`.
+    ///
+    /// `weight = start_y + (end_y - start_y) * (1 - e^(-rate * x)) / (1 - e^(-rate))`
+    /// where `x = (at - start_x) / (end_x - start_x)`
+    Exponential {
+        start_x: BlockNumber,
+        end_x: BlockNumber,
+        start_y: LBPWeight,
+        end_y: LBPWeight,
+        rate: FixedBalance,
+    },
+    /// Weight changes from `start_y` to `end_y` in `steps` equal jumps at evenly spaced blocks.
+    /// Weight stays constant between the jumps. Zero `steps` means the weight changes continuously,
+    /// which is equivalent to `Linear`.
+    Stepwise {
+        start_x: BlockNumber,
+        end_x: BlockNumber,
+        start_y: LBPWeight,
+        end_y: LBPWeight,
+        steps: u32,
+    },
+    /// Linear interpolation between consecutive `(block, weight)` checkpoints ordered by block.
+    PiecewiseLinear(Vec<(BlockNumber, LBPWeight)>),
+}
+
+/// Calculating weight at any given block according to the weight schedule.
+///
+/// - `schedule` - shape of the weight curve
+/// - `at` - block number at which to calculate the weight
+///
+/// Returns `ZeroDuration` if the interval of the schedule is empty and `OutOfRange` if `at` is outside of it.
+pub fn calculate_weights<BlockNumber>(
+    schedule: &WeightSchedule<BlockNumber>,
+    at: BlockNumber,
+) -> Result<LBPWeight, MathError>
+where
+    BlockNumber: num_traits::CheckedSub + TryInto<u32> + TryInto<u128> + PartialOrd + Copy,
+{
+    match schedule {
+        WeightSchedule::Linear {
+            start_x,
+            end_x,
+            start_y,
+            end_y,
+        } => {
+            interval_offset(*start_x, *end_x, at)?;
+            calculate_linear_weights(*start_x, *end_x, *start_y, *end_y, at)
+        }
+        WeightSchedule::Exponential {
+            start_x,
+            end_x,
+            start_y,
+            end_y,
+            rate,
+        } => calculate_exponential_weights(*start_x, *end_x, *start_y, *end_y, *rate, at),
+        WeightSchedule::Stepwise {
+            start_x,
+            end_x,
+            start_y,
+            end_y,
+            steps,
+        } => calculate_stepwise_weights(*start_x, *end_x, *start_y, *end_y, *steps, at),
+        WeightSchedule::PiecewiseLinear(checkpoints) => calculate_piecewise_linear_weights(checkpoints, at),
+    }
+}
+
+/// Calculating weight at any given block in an interval using exponential interpolation.
+///
+/// - `start_x` - beginning of an interval
+/// - `end_x` - end of an interval
+/// - `start_y` - initial weight
+/// - `end_y` - final weight
+/// - `rate` - steepness of the curve
+/// - `at` - block number at which to calculate the weight
+pub fn calculate_exponential_weights<BlockNumber: num_traits::CheckedSub + TryInto<u32> + TryInto<u128>>(
+    start_x: BlockNumber,
+    end_x: BlockNumber,
+    start_y: LBPWeight,
+    end_y: LBPWeight,
+    rate: FixedBalance,
+    at: BlockNumber,
+) -> Result<LBPWeight, MathError> {
+    let (d2, dx) = interval_offset(start_x, end_x, at)?;
+
+    // Endpoints are returned as they are to avoid any rounding
+    if d2 == 0 {
+        return Ok(start_y);
+    } else if d2 == dx {
+        return Ok(end_y);
+    } else if rate.is_zero() {
+        return calculate_linear_weights(0u32, dx, start_y, end_y, d2);
+    }
+
+    let x = FixedBalance::from_num(d2)
+        .checked_div(FixedBalance::from_num(dx))
+        .ok_or(Overflow)?;
+    let rate_x = rate.checked_mul(x).ok_or(Overflow)?;
+
+    let exp_rate_x: FixedBalance = crate::transcendental::exp(rate_x, true).map_err(|_| Overflow)?;
+    let exp_rate: FixedBalance = crate::transcendental::exp(rate, true).map_err(|_| Overflow)?;
+
+    let one = FixedBalance::from_num(1);
+    let numerator = one.checked_sub(exp_rate_x).ok_or(Overflow)?;
+    let denominator = one.checked_sub(exp_rate).ok_or(Overflow)?;
+    // Progress along the curve is kept within [0, 1] to stay within the interval of weights
+    let progress = numerator.checked_div(denominator).ok_or(Overflow)?.min(one);
+
+    let (diff, increasing) = if end_y >= start_y {
+        (end_y - start_y, true)
+    } else {
+        (start_y - end_y, false)
+    };
+
+    let delta: LBPWeight = FixedBalance::from_num(diff)
+        .checked_mul(progress)
+        .ok_or(Overflow)?
+        .to_num();
+
+    if increasing {
+        start_y.checked_add(delta).ok_or(Overflow)
+    } else {
+        start_y.checked_sub(delta).ok_or(Overflow)
+    }
+}
+
+/// Calculating weight at any given block in an interval changing the weight in equal steps.
+///
+/// - `start_x` - beginning of an interval
+/// - `end_x` - end of an interval
+/// - `start_y` - initial weight
+/// - `end_y` - final weight
+/// - `steps` - number of weight changes in the interval, zero for continuous linear change
+/// - `at` - block number at which to calculate the weight
+pub fn calculate_stepwise_weights<BlockNumber: num_traits::CheckedSub + TryInto<u32> + TryInto<u128>>(
+    start_x: BlockNumber,
+    end_x: BlockNumber,
+    start_y: LBPWeight,
+    end_y: LBPWeight,
+    steps: u32,
+    at: BlockNumber,
+) -> Result<LBPWeight, MathError> {
+    let (d2, dx) = interval_offset(start_x, end_x, at)?;
+
+    if steps.is_zero() {
+        return calculate_linear_weights(0u32, dx, start_y, end_y, d2);
+    }
+
+    // number of steps already taken
+    let taken = to_u256!(d2)
+        .checked_mul(steps.into())
+        .ok_or(Overflow)?
+        .checked_div(dx.into())
+        .ok_or(Overflow)?;
+    let (start_y, end_y, steps) = to_u256!(start_y, end_y, steps);
+    // Bare math is safe because `d2 <= dx`
+    let remaining = steps - taken;
+
+    let left_part = start_y.checked_mul(remaining).ok_or(Overflow)?;
+    let right_part = end_y.checked_mul(taken).ok_or(Overflow)?;
+    let result = (left_part.checked_add(right_part).ok_or(Overflow)?)
+        .checked_div(steps)
+        .ok_or(Overflow)?;
+
+    to_lbp_weight!(result)
+}
+
+/// Calculating weight at any given block using linear interpolation between checkpoints.
+///
+/// - `checkpoints` - `(block, weight)` pairs ordered by block
+/// - `at` - block number at which to calculate the weight
+pub fn calculate_piecewise_linear_weights<BlockNumber>(
+    checkpoints: &[(BlockNumber, LBPWeight)],
+    at: BlockNumber,
+) -> Result<LBPWeight, MathError>
+where
+    BlockNumber: num_traits::CheckedSub + TryInto<u32> + TryInto<u128> + PartialOrd + Copy,
+{
+    ensure!(checkpoints.len() >= 2, ZeroDuration);
+
+    let segment = checkpoints.windows(2).find(|w| at <= w[1].0).ok_or(OutOfRange)?;
+
+    let ((start_x, start_y), (end_x, end_y)) = (segment[0], segment[1]);
+    interval_offset(start_x, end_x, at)?;
+
+    calculate_linear_weights(start_x, end_x, start_y, end_y, at)
+}
+
+/// Offset of `at` from `start_x` and length of the interval `[start_x, end_x]`.
+///
+/// Returns `ZeroDuration` if the interval is empty and `OutOfRange` if `at` is outside of it.
+fn interval_offset<BlockNumber: num_traits::CheckedSub + TryInto<u32> + TryInto<u128>>(
+    start_x: BlockNumber,
+    end_x: BlockNumber,
+    at: BlockNumber,
+) -> Result<(u32, u32), MathError> {
+    let dx = end_x.checked_sub(&start_x).ok_or(ZeroDuration)?;
+    let d2 = at.checked_sub(&start_x).ok_or(OutOfRange)?;
+
+    let dx: u32 = dx.try_into().map_err(|_| Overflow)?;
+    ensure!(dx != 0, ZeroDuration);
+    // if dx fits into u32, any d2 which does not is out of range
+    let d2: u32 = d2.try_into().map_err(|_| OutOfRange)?;
+    ensure!(d2 <= dx, OutOfRange);
+
+    Ok((d2, dx))
+}
+
+/// Projecting spot price of the sold asset over the course of the sale.
+///
+/// Spot price of one unit (`HYDRA_ONE`) of the out asset denominated in the in asset is calculated
+/// every `step` blocks and at the end of the sale. Weights change linearly between the start and end weights.
+///
+/// Optionally, `buy_pressure` amount of the in asset is sold to the pool in every block of the sale.
+/// Price at a block is observed before the trade of that block.
+///
+/// - `reserves` - reserves of the in asset and the out asset
+/// - `start_weights` - initial weights of the in asset and the out asset
+/// - `end_weights` - final weights of the in asset and the out asset
+/// - `start` - first block of the sale
+/// - `end` - last block of the sale
+/// - `step` - number of blocks between projected points
+/// - `buy_pressure` - amount of the in asset sold to the pool every block
+///
+/// Returns `(block, spot_price)` points.
+pub fn project_price_path<BlockNumber>(
+    reserves: (Balance, Balance),
+    start_weights: (LBPWeight, LBPWeight),
+    end_weights: (LBPWeight, LBPWeight),
+    start: BlockNumber,
+    end: BlockNumber,
+    step: u32,
+    buy_pressure: Option<Balance>,
+) -> Result<Vec<(BlockNumber, Balance)>, MathError>
+where
+    BlockNumber: num_traits::CheckedSub + num_traits::CheckedAdd + TryInto<u32> + TryInto<u128> + From<u32> + Copy,
+{
+    ensure!(step != 0, ZeroDuration);
+
+    let duration: u32 = end
+        .checked_sub(&start)
+        .ok_or(Overflow)?
+        .try_into()
+        .map_err(|_| Overflow)?;
+    ensure!(duration != 0, ZeroDuration);
+
+    let (mut in_reserve, mut out_reserve) = reserves;
+    let mut path = Vec::new();
+
+    let mut offset = 0u32;
+    loop {
+        let at = start.checked_add(&BlockNumber::from(offset)).ok_or(Overflow)?;
+        let in_weight = calculate_linear_weights(start, end, start_weights.0, end_weights.0, at)?;
+        let out_weight = calculate_linear_weights(start, end, start_weights.1, end_weights.1, at)?;
+
+        if offset % step == 0 || offset == duration {
+            let price = calculate_spot_price(out_reserve, in_reserve, out_weight, in_weight, HYDRA_ONE)?;
+            path.push((at, price));
+        }
+
+        if offset == duration {
+            break;
+        }
+
+        let next_offset = match buy_pressure {
+            Some(amount) => {
+                let amount_out = calculate_out_given_in(in_reserve, out_reserve, in_weight, out_weight, amount)?;
+                in_reserve = in_reserve.checked_add(amount).ok_or(Overflow)?;
+                out_reserve = out_reserve.checked_sub(amount_out).ok_or(Overflow)?;
+                offset + 1
+            }
+            // Without trades only the projected points need to be visited
+            None => offset.saturating_add(step - offset % step),
+        };
+        offset = next_offset.min(duration);
+    }
+
+    Ok(path)
+}
+
+/// Change of pool reserves caused by the pool owner adding or removing liquidity.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LiquidityChange {
+    /// Amount of asset a added to or removed from the pool
+    pub delta_a: Balance,
+    /// Amount of asset b added to or removed from the pool
+    pub delta_b: Balance,
+    /// Spot price of asset a denominated in asset b after the change
+    pub spot_price: Ratio,
+}
+
+/// Calculating the change of a pool when adding liquidity of asset a only.
+/// Adding a single asset moves the spot price of asset a down.
+///
+/// To add asset b only, swap the order of reserves and weights.
+///
+/// - `reserve_a` - reserve amount of asset a
+/// - `reserve_b` - reserve amount of asset b
+/// - `weight_a` - current pool weight of asset a
+/// - `weight_b` - current pool weight of asset b
+/// - `amount_a` - amount of asset a added
+///
+/// Returns MathError in case of error
+pub fn calculate_single_sided_add(
+    reserve_a: Balance,
+    reserve_b: Balance,
+    weight_a: LBPWeight,
+    weight_b: LBPWeight,
+    amount_a: Balance,
+) -> Result<LiquidityChange, MathError> {
+    let new_reserve_a = reserve_a.checked_add(amount_a).ok_or(Overflow)?;
+
+    Ok(LiquidityChange {
+        delta_a: amount_a,
+        delta_b: 0,
+        spot_price: spot_price_ratio(new_reserve_a, reserve_b, weight_a, weight_b)?,
+    })
+}
+
+/// Calculating the change of a pool when adding liquidity of both assets without moving the spot price.
+/// Formula : DELTA_B = AMOUNT_A * RESERVE_B / RESERVE_A
+///
+/// Amount of asset b is rounded up.
+///
+/// - `reserve_a` - reserve amount of asset a
+/// - `reserve_b` - reserve amount of asset b
+/// - `weight_a` - current pool weight of asset a
+/// - `weight_b` - current pool weight of asset b
+/// - `amount_a` - amount of asset a added
+///
+/// Returns MathError in case of error
+pub fn calculate_proportional_add(
+    reserve_a: Balance,
+    reserve_b: Balance,
+    weight_a: LBPWeight,
+    weight_b: LBPWeight,
+    amount_a: Balance,
+) -> Result<LiquidityChange, MathError> {
+    ensure!(reserve_a != 0, ZeroReserve);
+
+    let (amount_hp, reserve_a_hp, reserve_b_hp) = to_u256!(amount_a, reserve_a, reserve_b);
+
+    // We round this up to make sure the pool does not lose value
+    let delta_b = amount_hp
+        .checked_mul(reserve_b_hp)
+        .and_then(|v| v.checked_add(reserve_a_hp.checked_sub(U256::one())?))
+        .and_then(|v| v.checked_div(reserve_a_hp))
+        .ok_or(Overflow)?;
+    let delta_b = to_balance!(delta_b)?;
+
+    let new_reserve_a = reserve_a.checked_add(amount_a).ok_or(Overflow)?;
+    let new_reserve_b = reserve_b.checked_add(delta_b).ok_or(Overflow)?;
+
+    Ok(LiquidityChange {
+        delta_a: amount_a,
+        delta_b,
+        spot_price: spot_price_ratio(new_reserve_a, new_reserve_b, weight_a, weight_b)?,
+    })
+}
+
+/// Calculating the change of a pool when removing liquidity of both assets without moving the spot price.
+/// Formula : DELTA_B = AMOUNT_A * RESERVE_B / RESERVE_A
+///
+/// Amount of asset b is rounded down. If all liquidity is removed, spot price is zero.
+///
+/// - `reserve_a` - reserve amount of asset a
+/// - `reserve_b` - reserve amount of asset b
+/// - `weight_a` - current pool weight of asset a
+/// - `weight_b` - current pool weight of asset b
+/// - `amount_a` - amount of asset a removed
+///
+/// Returns MathError in case of error
+pub fn calculate_remove_liquidity(
+    reserve_a: Balance,
+    reserve_b: Balance,
+    weight_a: LBPWeight,
+    weight_b: LBPWeight,
+    amount_a: Balance,
+) -> Result<LiquidityChange, MathError> {
+    ensure!(reserve_a != 0, ZeroReserve);
+    ensure!(amount_a <= reserve_a, InsufficientOutReserve);
+
+    let (amount_hp, reserve_a_hp, reserve_b_hp) = to_u256!(amount_a, reserve_a, reserve_b);
+
+    // We round this down to make sure the pool does not lose value
+    let delta_b = amount_hp
+        .checked_mul(reserve_b_hp)
+        .and_then(|v| v.checked_div(reserve_a_hp))
+        .ok_or(Overflow)?;
+    let delta_b = to_balance!(delta_b)?;
+
+    // Bare math is safe because `amount_a <= reserve_a` and so `delta_b <= reserve_b`
+    let new_reserve_a = reserve_a - amount_a;
+    let new_reserve_b = reserve_b - delta_b;
+
+    let spot_price = if new_reserve_a == 0 {
+        Ratio::zero()
+    } else {
+        spot_price_ratio(new_reserve_a, new_reserve_b, weight_a, weight_b)?
+    };
+
+    Ok(LiquidityChange {
+        delta_a: amount_a,
+        delta_b,
+        spot_price,
+    })
+}
@@ -1,5 +1,6 @@
 #![allow(clippy::module_inception)]
 
+pub mod fees;
 mod lbp;
 
 pub use lbp::*;
@@ -1,7 +1,11 @@
+use crate::lbp::fees;
 use crate::lbp::lbp;
+use crate::lbp::{LiquidityChange, WeightSchedule};
 
-use crate::types::{Balance, LBPWeight, HYDRA_ONE};
-use crate::MathError::{Overflow, ZeroDuration, ZeroReserve, ZeroWeight};
+use crate::types::{Balance, FixedBalance, LBPWeight, Ratio, HYDRA_ONE};
+use crate::MathError::{
+    InsufficientOutReserve, InvalidFee, OutOfRange, Overflow, ZeroDuration, ZeroReserve, ZeroWeight,
+};
 
 use std::vec;
 
@@ -35,6 +39,41 @@ fn spot_price_should_work() {
     }
 }
 
+#[test]
+fn spot_price_ratio_should_work() {
+    let cases = vec![
+        (1000, 2000, 500, 500, Ok(Ratio::new(1_000_000, 500_000)), "Easy case"),
+        (3, 1, 1, 1, Ok(Ratio::new(1, 3)), "Price below one"),
+        (4_000, 1_000, 80, 20, Ok(Ratio::new(80_000, 80_000)), "Weighted price"),
+        (0, 0, 0, 0, Err(ZeroReserve), "Zero reserves and weights"),
+        (0, 1, 1, 1, Err(ZeroReserve), "Zero sell_reserve"),
+        (1, 1, 1, 0, Err(ZeroWeight), "Zero buy_weight"),
+        (1, 0, 1, 1, Ok(Ratio::zero()), "Zero buy_reserve"),
+        (1, 1, 0, 1, Ok(Ratio::zero()), "Zero sell_weight"),
+        (
+            1,
+            Balance::MAX,
+            LBPWeight::MAX,
+            1,
+            Ok(Ratio::new(340282366841710300949110269838224261119, 1)),
+            "Rounded numerator",
+        ),
+    ];
+
+    for case in cases {
+        assert_eq!(
+            lbp::spot_price_ratio(case.0, case.1, case.2, case.3),
+            case.4,
+            "{}",
+            case.5
+        );
+    }
+
+    // small amounts lose precision when the price is scaled by the amount
+    assert_eq!(lbp::calculate_spot_price(3, 1, 1, 1, 1), Ok(0));
+    assert!(!lbp::spot_price_ratio(3, 1, 1, 1).unwrap().is_zero());
+}
+
 #[test]
 fn out_given_in_should_work() {
     let cases = vec![
@@ -226,3 +265,304 @@ fn linear_weights_should_work() {
         );
     }
 }
+
+#[test]
+fn calculate_weights_should_work() {
+    let linear = WeightSchedule::Linear {
+        start_x: 100u32,
+        end_x: 200u32,
+        start_y: 1_000,
+        end_y: 2_000,
+    };
+    let exponential = WeightSchedule::Exponential {
+        start_x: 100u32,
+        end_x: 200u32,
+        start_y: 1_000,
+        end_y: 2_000,
+        rate: FixedBalance::from_num(2),
+    };
+    let exponential_decreasing = WeightSchedule::Exponential {
+        start_x: 100u32,
+        end_x: 200u32,
+        start_y: 2_000,
+        end_y: 1_000,
+        rate: FixedBalance::from_num(2),
+    };
+    let exponential_zero_rate = WeightSchedule::Exponential {
+        start_x: 100u32,
+        end_x: 200u32,
+        start_y: 1_000,
+        end_y: 2_000,
+        rate: FixedBalance::from_num(0),
+    };
+    let stepwise = WeightSchedule::Stepwise {
+        start_x: 100u32,
+        end_x: 200u32,
+        start_y: 1_000,
+        end_y: 2_000,
+        steps: 4,
+    };
+    let stepwise_zero_steps = WeightSchedule::Stepwise {
+        start_x: 100u32,
+        end_x: 200u32,
+        start_y: 1_000,
+        end_y: 2_000,
+        steps: 0,
+    };
+    let piecewise = WeightSchedule::PiecewiseLinear(vec![(100u32, 1_000), (150u32, 3_000), (200u32, 2_000)]);
+    let single_checkpoint = WeightSchedule::PiecewiseLinear(vec![(100u32, 1_000)]);
+    let empty_interval = WeightSchedule::Exponential {
+        start_x: 100u32,
+        end_x: 100u32,
+        start_y: 1_000,
+        end_y: 2_000,
+        rate: FixedBalance::from_num(2),
+    };
+    let inverted_interval = WeightSchedule::Stepwise {
+        start_x: 200u32,
+        end_x: 100u32,
+        start_y: 1_000,
+        end_y: 2_000,
+        steps: 4,
+    };
+
+    let cases = vec![
+        (&linear, 150u32, Ok(1_500), "Linear"),
+        (&linear, 99u32, Err(OutOfRange), "Linear before start"),
+        (&linear, 201u32, Err(OutOfRange), "Linear after end"),
+        (&exponential, 100u32, Ok(1_000), "Exponential start"),
+        (&exponential, 150u32, Ok(1_731), "Exponential middle"),
+        (&exponential, 200u32, Ok(2_000), "Exponential end"),
+        (&exponential_decreasing, 150u32, Ok(1_269), "Exponential decreasing"),
+        (&exponential_decreasing, 200u32, Ok(1_000), "Exponential decreasing end"),
+        (&exponential_zero_rate, 150u32, Ok(1_500), "Exponential with zero rate"),
+        (&exponential, 99u32, Err(OutOfRange), "Exponential before start"),
+        (&exponential, 201u32, Err(OutOfRange), "Exponential after end"),
+        (&empty_interval, 100u32, Err(ZeroDuration), "Exponential zero duration"),
+        (
+            &inverted_interval,
+            150u32,
+            Err(ZeroDuration),
+            "Stepwise inverted interval",
+        ),
+        (&stepwise, 100u32, Ok(1_000), "Stepwise start"),
+        (&stepwise, 124u32, Ok(1_000), "Stepwise before first step"),
+        (&stepwise, 125u32, Ok(1_250), "Stepwise first step"),
+        (&stepwise, 150u32, Ok(1_500), "Stepwise second step"),
+        (&stepwise, 199u32, Ok(1_750), "Stepwise before last step"),
+        (&stepwise, 200u32, Ok(2_000), "Stepwise end"),
+        (&stepwise_zero_steps, 130u32, Ok(1_300), "Stepwise zero steps is linear"),
+        (&piecewise, 100u32, Ok(1_000), "Piecewise start"),
+        (&piecewise, 125u32, Ok(2_000), "Piecewise first segment"),
+        (&piecewise, 150u32, Ok(3_000), "Piecewise checkpoint"),
+        (&piecewise, 175u32, Ok(2_500), "Piecewise second segment"),
+        (&piecewise, 200u32, Ok(2_000), "Piecewise end"),
+        (&piecewise, 99u32, Err(OutOfRange), "Piecewise before start"),
+        (&piecewise, 201u32, Err(OutOfRange), "Piecewise after end"),
+        (&single_checkpoint, 100u32, Err(ZeroDuration), "Single checkpoint"),
+    ];
+
+    for case in cases {
+        assert_eq!(lbp::calculate_weights(case.0, case.1), case.2, "{}", case.3);
+    }
+}
+
+#[test]
+fn project_price_path_should_work() {
+    let reserves = (1_000 * HYDRA_ONE, 4_000 * HYDRA_ONE);
+    let start_weights = (20_000_000, 80_000_000);
+    let end_weights = (80_000_000, 20_000_000);
+
+    assert_eq!(
+        lbp::project_price_path(reserves, start_weights, end_weights, 100u32, 200u32, 50, None),
+        Ok(vec![(100, HYDRA_ONE), (150, 250_000_000_000), (200, 62_500_000_000)])
+    );
+    assert_eq!(
+        lbp::project_price_path(reserves, start_weights, end_weights, 100u64, 200u64, 40, None),
+        Ok(vec![
+            (100, HYDRA_ONE),
+            (140, 318_181_818_181),
+            (180, 117_647_058_823),
+            (200, 62_500_000_000)
+        ])
+    );
+    assert_eq!(
+        lbp::project_price_path(reserves, start_weights, start_weights, 100u32, 200u32, 1_000, None),
+        Ok(vec![(100, HYDRA_ONE), (200, HYDRA_ONE)])
+    );
+
+    assert_eq!(
+        lbp::project_price_path(reserves, start_weights, end_weights, 100u32, 200u32, 0, None),
+        Err(ZeroDuration)
+    );
+    assert_eq!(
+        lbp::project_price_path(reserves, start_weights, end_weights, 100u32, 100u32, 10, None),
+        Err(ZeroDuration)
+    );
+    assert_eq!(
+        lbp::project_price_path(reserves, start_weights, end_weights, 200u32, 100u32, 10, None),
+        Err(Overflow)
+    );
+    assert_eq!(
+        lbp::project_price_path(
+            (0, 4_000 * HYDRA_ONE),
+            start_weights,
+            end_weights,
+            100u32,
+            200u32,
+            10,
+            None
+        ),
+        Ok(vec![
+            (100, 0),
+            (110, 0),
+            (120, 0),
+            (130, 0),
+            (140, 0),
+            (150, 0),
+            (160, 0),
+            (170, 0),
+            (180, 0),
+            (190, 0),
+            (200, 0)
+        ])
+    );
+}
+
+#[test]
+fn project_price_path_with_buy_pressure_should_work() {
+    let reserves = (1_000 * HYDRA_ONE, 4_000 * HYDRA_ONE);
+    let start_weights = (20_000_000, 80_000_000);
+    let end_weights = (80_000_000, 20_000_000);
+
+    let without_pressure =
+        lbp::project_price_path(reserves, start_weights, end_weights, 100u32, 200u32, 10, None).unwrap();
+    let with_pressure = lbp::project_price_path(
+        reserves,
+        start_weights,
+        end_weights,
+        100u32,
+        200u32,
+        10,
+        Some(HYDRA_ONE),
+    )
+    .unwrap();
+
+    assert_eq!(with_pressure.len(), without_pressure.len());
+    assert_eq!(with_pressure[0], without_pressure[0]);
+    for (projected, idle) in with_pressure.iter().zip(without_pressure.iter()).skip(1) {
+        assert_eq!(projected.0, idle.0);
+        assert!(projected.1 > idle.1);
+    }
+
+    // with constant weights, buying only pushes the price up
+    let constant_weights = lbp::project_price_path(
+        reserves,
+        start_weights,
+        start_weights,
+        100u32,
+        200u32,
+        10,
+        Some(HYDRA_ONE),
+    )
+    .unwrap();
+    assert!(constant_weights.windows(2).all(|w| w[0].1 < w[1].1));
+}
+
+#[test]
+fn owner_liquidity_should_work() {
+    let change = |delta_a, delta_b, n, d| {
+        Ok(LiquidityChange {
+            delta_a,
+            delta_b,
+            spot_price: Ratio::new(n, d),
+        })
+    };
+
+    assert_eq!(
+        lbp::calculate_single_sided_add(1_000, 2_000, 50, 50, 1_000),
+        change(1_000, 0, 100_000, 100_000)
+    );
+    assert_eq!(lbp::calculate_single_sided_add(0, 2_000, 50, 50, 0), Err(ZeroReserve));
+
+    assert_eq!(
+        lbp::calculate_proportional_add(1_000, 3_000, 20, 80, 1_000),
+        change(1_000, 3_000, 120_000, 160_000)
+    );
+    assert_eq!(
+        lbp::calculate_proportional_add(3, 1_000, 20, 80, 1),
+        change(1, 334, 26_680, 320)
+    );
+    assert_eq!(lbp::calculate_proportional_add(0, 1_000, 20, 80, 1), Err(ZeroReserve));
+
+    assert_eq!(
+        lbp::calculate_remove_liquidity(3, 1_000, 20, 80, 1),
+        change(1, 333, 13_340, 160)
+    );
+    assert_eq!(
+        lbp::calculate_remove_liquidity(3, 1_000, 20, 80, 3),
+        change(3, 1_000, 0, 1)
+    );
+    assert_eq!(
+        lbp::calculate_remove_liquidity(3, 1_000, 20, 80, 4),
+        Err(InsufficientOutReserve)
+    );
+    assert_eq!(lbp::calculate_remove_liquidity(0, 1_000, 20, 80, 0), Err(ZeroReserve));
+}
+
+#[test]
+fn fees_should_work() {
+    let cases = vec![
+        (1_000, 0, 1_000, (2, 10), (1, 100), Ok((800, 0, 200)), "Before target"),
+        (
+            1_000,
+            1_000,
+            1_000,
+            (2, 10),
+            (1, 100),
+            Ok((990, 10, 0)),
+            "Target reached",
+        ),
+        (
+            1_000,
+            2_000,
+            1_000,
+            (2, 10),
+            (1, 100),
+            Ok((990, 10, 0)),
+            "Target exceeded",
+        ),
+        (
+            1_000,
+            900,
+            1_000,
+            (2, 10),
+            (1, 100),
+            Ok((895, 5, 100)),
+            "Crossing target",
+        ),
+        (1_001, 0, 1_000, (2, 10), (1, 100), Ok((800, 0, 201)), "Fee rounded up"),
+        (
+            1_000,
+            0,
+            1,
+            (3, 1_000),
+            (1, 100),
+            Ok((992, 7, 1)),
+            "Crossing target rounded",
+        ),
+        (10, 0, 3, (1, 1), (1, 1), Ok((0, 7, 3)), "Full fees"),
+        (1_000, 0, 100, (0, 0), (0, 0), Ok((1_000, 0, 0)), "Zero fees"),
+        (0, 0, 100, (2, 10), (1, 100), Ok((0, 0, 0)), "Zero amount"),
+        (1_000, 0, 100, (11, 10), (1, 100), Err(InvalidFee), "Fee above 100%"),
+    ];
+
+    for case in cases {
+        assert_eq!(
+            fees::calculate_fees(case.0, case.1, case.2, case.3, case.4),
+            case.5,
+            "{}",
+            case.6
+        );
+    }
+}
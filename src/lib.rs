@@ -15,6 +15,7 @@ extern crate approx;
 #[cfg(test)]
 mod test_pow_accuracy;
 
+pub mod clmm;
 pub mod ema;
 pub mod fee;
 pub mod fraction;
@@ -29,6 +30,7 @@ pub mod support;
 pub mod test_utils;
 pub mod transcendental;
 pub mod types;
+pub mod weighted_pool;
 pub mod xyk;
 
 #[macro_export]
@@ -86,6 +88,9 @@ pub enum MathError {
     ZeroReserve,
     ZeroDuration,
     DivisionByZero,
+    InvalidFee,
+    InvalidAssetIndex,
+    OutOfRange,
 }
 
 #[cfg(test)]
@@ -0,0 +1,349 @@
+use crate::liquidity_mining::{
+    calculate_accumulated_rps, calculate_global_farm_rewards, calculate_global_farm_shares,
+    calculate_loyalty_curve_multiplier, calculate_reward, calculate_user_reward, calculate_valued_shares, LoyaltyCurve,
+};
+use crate::types::Balance;
+use crate::MathError;
+use crate::MathError::Overflow;
+
+use core::convert::TryInto;
+use num_traits::{CheckedSub, Zero};
+use sp_arithmetic::{traits::CheckedAdd, FixedPointNumber, FixedU128};
+
+/// Reward currency of a global farm with its own yield and budget.
+///
+/// Rewards move from `remaining_rewards` to `pending_rewards` on `update`, from there to the yield
+/// farms and finally to the users on `claim`, so the sum of the buckets always equals the budget.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RewardCurrency {
+    pub yield_per_period: FixedU128,
+    pub max_reward_per_period: Balance,
+    pub price_adjustment: FixedU128,
+    /// Accumulated rewards per share `z` of the global farm.
+    pub accumulated_rpz: FixedU128,
+    /// Rewards of the budget which have not been distributed yet.
+    pub remaining_rewards: Balance,
+    /// Rewards distributed to the global farm but not yet claimed by yield farms.
+    pub pending_rewards: Balance,
+    /// Rewards paid out to users.
+    pub paid_rewards: Balance,
+}
+
+impl RewardCurrency {
+    pub fn new(
+        budget: Balance,
+        yield_per_period: FixedU128,
+        max_reward_per_period: Balance,
+        price_adjustment: FixedU128,
+    ) -> Self {
+        Self {
+            yield_per_period,
+            max_reward_per_period,
+            price_adjustment,
+            accumulated_rpz: FixedU128::zero(),
+            remaining_rewards: budget,
+            pending_rewards: 0,
+            paid_rewards: 0,
+        }
+    }
+}
+
+/// This function calculates the rewards of each of the `reward_currencies` for the shares
+/// `total_shares_z` of a global farm, capped by the remaining budget of the currency, or error.
+pub fn calculate_global_farm_rewards_per_currency<
+    Period: Copy + num_traits::CheckedSub + TryInto<u32> + TryInto<u128>,
+    const N: usize,
+>(
+    total_shares_z: Balance,
+    reward_currencies: &[RewardCurrency; N],
+    periods_since_last_update: Period,
+) -> Result<[Balance; N], MathError> {
+    let mut rewards = [0; N];
+    for (reward, currency) in rewards.iter_mut().zip(reward_currencies.iter()) {
+        *reward = calculate_global_farm_rewards(
+            total_shares_z,
+            currency.price_adjustment,
+            currency.yield_per_period,
+            currency.max_reward_per_period,
+            periods_since_last_update,
+        )?
+        .min(currency.remaining_rewards);
+    }
+    Ok(rewards)
+}
+
+/// State of a global farm which distributes `N` reward currencies from fixed budgets to its yield
+/// farms. All reward currencies are distributed to the same shares `total_shares_z`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GlobalFarm<Period, const N: usize = 1> {
+    pub updated_at: Period,
+    /// Sum of the stakes of all yield farms in the global farm.
+    pub total_shares_z: Balance,
+    pub reward_currencies: [RewardCurrency; N],
+}
+
+/// State of a yield farm which receives `N` reward currencies from a global farm for the
+/// deposited shares.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct YieldFarm<Period, const N: usize = 1> {
+    pub updated_at: Period,
+    /// Multiplier of the valued shares of new deposits, see `Deposit::stake_in_global_farm`.
+    pub multiplier: FixedU128,
+    pub loyalty_curve: Option<LoyaltyCurve>,
+    pub total_shares: Balance,
+    pub total_valued_shares: Balance,
+    /// Sum of the stakes of all deposits in the global farm.
+    pub total_stake_in_global_farm: Balance,
+    /// Accumulated rewards per valued share of each reward currency.
+    pub accumulated_rpvs: [FixedU128; N],
+    /// Accumulated rewards per share `z` of the global farm at the last update.
+    pub accumulated_rpz: [FixedU128; N],
+    /// Rewards claimed from the global farm but not yet claimed by users.
+    pub left_to_distribute: [Balance; N],
+}
+
+/// Deposit of shares into a yield farm.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Deposit<Period, const N: usize = 1> {
+    pub shares: Balance,
+    pub valued_shares: Balance,
+    /// Shares `z` of the deposit in the global farm, i.e. the valued shares scaled by the
+    /// multiplier of the yield farm when the deposit was made.
+    pub stake_in_global_farm: Balance,
+    /// Accumulated rewards per valued share of the yield farm when the deposit was made.
+    pub accumulated_rpvs: [FixedU128; N],
+    pub accumulated_claimed_rewards: [Balance; N],
+    pub entered_at: Period,
+}
+
+impl<Period, const N: usize> GlobalFarm<Period, N>
+where
+    Period: Copy + PartialOrd + CheckedSub + TryInto<u32> + TryInto<u128>,
+{
+    pub fn new(reward_currencies: [RewardCurrency; N], now: Period) -> Self {
+        Self {
+            updated_at: now,
+            total_shares_z: 0,
+            reward_currencies,
+        }
+    }
+
+    /// Distribute the rewards of every reward currency for the periods since the last update to
+    /// the shares in the farm. Rewards are capped by the remaining budget of the currency.
+    pub fn update(&mut self, now: Period) -> Result<(), MathError> {
+        if now <= self.updated_at {
+            return Ok(());
+        }
+        if self.total_shares_z.is_zero() {
+            self.updated_at = now;
+            return Ok(());
+        }
+
+        let periods = now.checked_sub(&self.updated_at).ok_or(Overflow)?;
+        let rewards =
+            calculate_global_farm_rewards_per_currency(self.total_shares_z, &self.reward_currencies, periods)?;
+
+        for (currency, reward) in self.reward_currencies.iter_mut().zip(rewards) {
+            if reward.is_zero() {
+                continue;
+            }
+            currency.accumulated_rpz =
+                calculate_accumulated_rps(currency.accumulated_rpz, self.total_shares_z, reward)?;
+            currency.remaining_rewards = currency.remaining_rewards.checked_sub(reward).ok_or(Overflow)?;
+            currency.pending_rewards = currency.pending_rewards.checked_add(reward).ok_or(Overflow)?;
+        }
+        self.updated_at = now;
+        Ok(())
+    }
+
+    /// Sum of all reward buckets of each reward currency of the farm and the given yield farms
+    /// which equals the budget of the currency.
+    pub fn total_rewards(&self, yield_farms: &[YieldFarm<Period, N>]) -> Result<[Balance; N], MathError> {
+        let mut totals = [0; N];
+        for (i, (total, currency)) in totals.iter_mut().zip(self.reward_currencies.iter()).enumerate() {
+            *total = yield_farms.iter().try_fold(
+                currency
+                    .remaining_rewards
+                    .checked_add(currency.pending_rewards)
+                    .and_then(|sum| sum.checked_add(currency.paid_rewards))
+                    .ok_or(Overflow)?,
+                |sum, yield_farm| sum.checked_add(yield_farm.left_to_distribute[i]).ok_or(Overflow),
+            )?;
+        }
+        Ok(totals)
+    }
+}
+
+impl<Period, const N: usize> YieldFarm<Period, N>
+where
+    Period: Copy + PartialOrd + CheckedSub + TryInto<u32> + TryInto<u128>,
+{
+    pub fn new(
+        global_farm: &GlobalFarm<Period, N>,
+        multiplier: FixedU128,
+        loyalty_curve: Option<LoyaltyCurve>,
+        now: Period,
+    ) -> Self {
+        let mut accumulated_rpz = [FixedU128::zero(); N];
+        for (rpz, currency) in accumulated_rpz.iter_mut().zip(global_farm.reward_currencies.iter()) {
+            *rpz = currency.accumulated_rpz;
+        }
+        Self {
+            updated_at: now,
+            multiplier,
+            loyalty_curve,
+            total_shares: 0,
+            total_valued_shares: 0,
+            total_stake_in_global_farm: 0,
+            accumulated_rpvs: [FixedU128::zero(); N],
+            accumulated_rpz,
+            left_to_distribute: [0; N],
+        }
+    }
+
+    /// Update the global farm and claim the rewards of every reward currency of this yield farm
+    /// from it.
+    ///
+    /// Rewards are claimed for the stakes of the deposits which are part of `total_shares_z`, so
+    /// the yield farms never claim more than the global farm distributed.
+    pub fn update(&mut self, global_farm: &mut GlobalFarm<Period, N>, now: Period) -> Result<(), MathError> {
+        global_farm.update(now)?;
+
+        for (i, currency) in global_farm.reward_currencies.iter_mut().enumerate() {
+            if self.accumulated_rpz[i] == currency.accumulated_rpz {
+                continue;
+            }
+            if !self.total_valued_shares.is_zero() {
+                let rewards = calculate_reward(
+                    self.accumulated_rpz[i],
+                    currency.accumulated_rpz,
+                    self.total_stake_in_global_farm,
+                )?;
+                let delta_rpvs = FixedU128::checked_from_rational(rewards, self.total_valued_shares).ok_or(Overflow)?;
+
+                self.accumulated_rpvs[i] = self.accumulated_rpvs[i].checked_add(&delta_rpvs).ok_or(Overflow)?;
+                currency.pending_rewards = currency.pending_rewards.checked_sub(rewards).ok_or(Overflow)?;
+                self.left_to_distribute[i] = self.left_to_distribute[i].checked_add(rewards).ok_or(Overflow)?;
+            }
+            self.accumulated_rpz[i] = currency.accumulated_rpz;
+        }
+        self.updated_at = now;
+        Ok(())
+    }
+
+    /// Deposit `shares` into the yield farm. `incentivized_asset_balance` is used to value the
+    /// shares in the incentivized asset.
+    pub fn deposit(
+        &mut self,
+        global_farm: &mut GlobalFarm<Period, N>,
+        shares: Balance,
+        incentivized_asset_balance: Balance,
+        now: Period,
+    ) -> Result<Deposit<Period, N>, MathError> {
+        self.update(global_farm, now)?;
+
+        let valued_shares = calculate_valued_shares(shares, incentivized_asset_balance)?;
+        let stake_in_global_farm = calculate_global_farm_shares(valued_shares, self.multiplier)?;
+
+        self.total_shares = self.total_shares.checked_add(shares).ok_or(Overflow)?;
+        self.total_valued_shares = self.total_valued_shares.checked_add(valued_shares).ok_or(Overflow)?;
+        self.total_stake_in_global_farm = self
+            .total_stake_in_global_farm
+            .checked_add(stake_in_global_farm)
+            .ok_or(Overflow)?;
+        global_farm.total_shares_z = global_farm
+            .total_shares_z
+            .checked_add(stake_in_global_farm)
+            .ok_or(Overflow)?;
+
+        Ok(Deposit {
+            shares,
+            valued_shares,
+            stake_in_global_farm,
+            accumulated_rpvs: self.accumulated_rpvs,
+            accumulated_claimed_rewards: [0; N],
+            entered_at: now,
+        })
+    }
+
+    /// Claim the rewards of `deposit` in every reward currency and return
+    /// `(claimed_rewards, unclaimable_rewards)` per reward currency.
+    pub fn claim(
+        &mut self,
+        global_farm: &mut GlobalFarm<Period, N>,
+        deposit: &mut Deposit<Period, N>,
+        now: Period,
+    ) -> Result<([Balance; N], [Balance; N]), MathError> {
+        self.update(global_farm, now)?;
+
+        let loyalty_multiplier = self.loyalty_multiplier(deposit, now)?;
+        let mut claimed_rewards = [0; N];
+        let mut unclaimable_rewards = [0; N];
+        for (i, currency) in global_farm.reward_currencies.iter_mut().enumerate() {
+            let (rewards, unclaimable) = calculate_user_reward(
+                deposit.accumulated_rpvs[i],
+                deposit.valued_shares,
+                deposit.accumulated_claimed_rewards[i],
+                self.accumulated_rpvs[i],
+                loyalty_multiplier,
+            )?;
+
+            deposit.accumulated_claimed_rewards[i] = deposit.accumulated_claimed_rewards[i]
+                .checked_add(rewards)
+                .ok_or(Overflow)?;
+            self.left_to_distribute[i] = self.left_to_distribute[i].checked_sub(rewards).ok_or(Overflow)?;
+            currency.paid_rewards = currency.paid_rewards.checked_add(rewards).ok_or(Overflow)?;
+            claimed_rewards[i] = rewards;
+            unclaimable_rewards[i] = unclaimable;
+        }
+        Ok((claimed_rewards, unclaimable_rewards))
+    }
+
+    /// Claim the rewards of `deposit` and remove its shares from the farm.
+    /// Unclaimable rewards are returned to the budget of the reward currency.
+    ///
+    /// Returns `(claimed_rewards, unclaimable_rewards)` per reward currency.
+    pub fn withdraw(
+        &mut self,
+        global_farm: &mut GlobalFarm<Period, N>,
+        mut deposit: Deposit<Period, N>,
+        now: Period,
+    ) -> Result<([Balance; N], [Balance; N]), MathError> {
+        let (rewards, unclaimable_rewards) = self.claim(global_farm, &mut deposit, now)?;
+
+        self.total_shares = self.total_shares.checked_sub(deposit.shares).ok_or(Overflow)?;
+        self.total_valued_shares = self
+            .total_valued_shares
+            .checked_sub(deposit.valued_shares)
+            .ok_or(Overflow)?;
+        self.total_stake_in_global_farm = self
+            .total_stake_in_global_farm
+            .checked_sub(deposit.stake_in_global_farm)
+            .ok_or(Overflow)?;
+        global_farm.total_shares_z = global_farm
+            .total_shares_z
+            .checked_sub(deposit.stake_in_global_farm)
+            .ok_or(Overflow)?;
+
+        for (i, currency) in global_farm.reward_currencies.iter_mut().enumerate() {
+            self.left_to_distribute[i] = self.left_to_distribute[i]
+                .checked_sub(unclaimable_rewards[i])
+                .ok_or(Overflow)?;
+            currency.remaining_rewards = currency
+                .remaining_rewards
+                .checked_add(unclaimable_rewards[i])
+                .ok_or(Overflow)?;
+        }
+        Ok((rewards, unclaimable_rewards))
+    }
+
+    fn loyalty_multiplier(&self, deposit: &Deposit<Period, N>, now: Period) -> Result<FixedU128, MathError> {
+        match self.loyalty_curve {
+            Some(curve) => {
+                let periods = now.checked_sub(&deposit.entered_at).ok_or(Overflow)?;
+                calculate_loyalty_curve_multiplier(periods, &curve)
+            }
+            None => Ok(FixedU128::from(1)),
+        }
+    }
+}
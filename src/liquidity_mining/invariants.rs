@@ -223,3 +223,140 @@ proptest! {
         );
     }
 }
+
+#[derive(Clone, Debug)]
+enum FarmAction {
+    /// Deposit `(yield_farm, shares, incentivized_asset_balance)`.
+    Deposit(usize, Balance, Balance),
+    Claim(usize),
+    Withdraw(usize),
+    ChangeMultiplier(usize, FixedU128),
+}
+
+fn farm_multiplier() -> impl Strategy<Value = FixedU128> {
+    (1..=10 * FixedU128::DIV).prop_map(FixedU128::from_inner)
+}
+
+fn farm_action() -> impl Strategy<Value = (u32, FarmAction)> {
+    (
+        0..20_u32,
+        prop_oneof![
+            (any::<usize>(), 1..1_000_000 * ONE, 1..1_000_u128)
+                .prop_map(|(yield_farm, shares, balance)| FarmAction::Deposit(yield_farm, shares, balance)),
+            any::<usize>().prop_map(FarmAction::Claim),
+            any::<usize>().prop_map(FarmAction::Withdraw),
+            (any::<usize>(), farm_multiplier())
+                .prop_map(|(yield_farm, multiplier)| FarmAction::ChangeMultiplier(yield_farm, multiplier)),
+        ],
+    )
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(1_000))]
+    #[test]
+    fn farm_rewards_should_not_exceed_budget(
+        budget in ONE..1_000_000 * ONE,
+        with_loyalty_curve in any::<bool>(),
+        multipliers in prop::collection::vec(farm_multiplier(), 1..5),
+        actions in prop::collection::vec(farm_action(), 1..50),
+    ) {
+        use crate::liquidity_mining::{GlobalFarm, LoyaltyCurve, RewardCurrency, YieldFarm};
+
+        let mut global_farm = GlobalFarm::new(
+            [RewardCurrency::new(budget, FixedU128::from_rational(1, 100), 1_000 * ONE, FixedU128::one())],
+            0_u32,
+        );
+        let loyalty_curve = with_loyalty_curve.then(|| LoyaltyCurve::Hyperbolic {
+            initial_reward_percentage: FixedU128::from_rational(1, 2),
+            scale_coef: 100,
+        });
+        let mut yield_farms: Vec<_> = multipliers
+            .into_iter()
+            .map(|multiplier| YieldFarm::new(&global_farm, multiplier, loyalty_curve, 0_u32))
+            .collect();
+
+        let mut deposits = Vec::new();
+        let mut now = 0_u32;
+        for (elapsed, action) in actions {
+            now += elapsed;
+            match action {
+                FarmAction::Deposit(i, shares, balance) => {
+                    let i = i % yield_farms.len();
+                    let deposit = yield_farms[i].deposit(&mut global_farm, shares, balance, now).unwrap();
+                    deposits.push((i, deposit));
+                }
+                FarmAction::Claim(i) if !deposits.is_empty() => {
+                    let (yield_farm, deposit) = &mut deposits[i % deposits.len()];
+                    yield_farms[*yield_farm].claim(&mut global_farm, deposit, now).unwrap();
+                }
+                FarmAction::Withdraw(i) if !deposits.is_empty() => {
+                    let (yield_farm, deposit) = deposits.swap_remove(i % deposits.len());
+                    yield_farms[yield_farm].withdraw(&mut global_farm, deposit, now).unwrap();
+                }
+                FarmAction::ChangeMultiplier(i, multiplier) => {
+                    let i = i % yield_farms.len();
+                    yield_farms[i].multiplier = multiplier;
+                }
+                _ => {}
+            }
+
+            let total_stake = yield_farms.iter().map(|yield_farm| yield_farm.total_stake_in_global_farm).sum::<Balance>();
+            prop_assert_eq!(total_stake, global_farm.total_shares_z);
+            prop_assert!(global_farm.reward_currencies[0].paid_rewards <= budget);
+            prop_assert_eq!(global_farm.total_rewards(&yield_farms).unwrap(), [budget]);
+        }
+    }
+}
+
+fn loyalty_curve() -> impl Strategy<Value = crate::liquidity_mining::LoyaltyCurve> {
+    use crate::liquidity_mining::LoyaltyCurve;
+
+    let initial_reward_percentage = (0..=ONE * 1_000_000).prop_map(FixedU128::from_inner);
+    prop_oneof![
+        (initial_reward_percentage.clone(), 0..=u32::MAX).prop_map(|(initial_reward_percentage, scale_coef)| {
+            LoyaltyCurve::Hyperbolic {
+                initial_reward_percentage,
+                scale_coef,
+            }
+        }),
+        (initial_reward_percentage.clone(), 0..=u32::MAX).prop_map(|(initial_reward_percentage, duration)| {
+            LoyaltyCurve::Linear {
+                initial_reward_percentage,
+                duration,
+            }
+        }),
+        (initial_reward_percentage.clone(), 0..=u32::MAX, 0..=u32::MAX).prop_map(
+            |(initial_reward_percentage, cliff, duration)| LoyaltyCurve::CliffLinear {
+                initial_reward_percentage,
+                cliff,
+                duration,
+            }
+        ),
+        (initial_reward_percentage, 0..=u32::MAX).prop_map(|(initial_reward_percentage, half_life)| {
+            LoyaltyCurve::Exponential {
+                initial_reward_percentage,
+                half_life,
+            }
+        }),
+    ]
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(10_000))]
+    #[test]
+    fn loyalty_curve_multiplier_should_be_monotonic(
+        curve in loyalty_curve(),
+        periods in periods(),
+        delta in periods(),
+    ) {
+        let later_periods = periods.saturating_add(delta);
+        let multiplier = crate::liquidity_mining::calculate_loyalty_curve_multiplier(periods, &curve).unwrap();
+        let later_multiplier = crate::liquidity_mining::calculate_loyalty_curve_multiplier(later_periods, &curve).unwrap();
+
+        // the division of the hyperbolic curve can round either way
+        let tolerance = FixedU128::from_inner(10);
+        prop_assert!(later_multiplier.checked_add(&tolerance).unwrap() >= multiplier);
+        prop_assert!(multiplier.checked_add(&tolerance).unwrap() >= curve.initial_reward_percentage());
+        prop_assert!(later_multiplier <= FixedU128::one());
+    }
+}
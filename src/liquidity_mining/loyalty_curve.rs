@@ -0,0 +1,133 @@
+use crate::liquidity_mining::calculate_loyalty_multiplier;
+use crate::transcendental::half_life_decay;
+use crate::MathError;
+use crate::MathError::Overflow;
+
+use core::convert::TryInto;
+use fixed::types::U64F64;
+use sp_arithmetic::{
+    traits::{CheckedAdd, CheckedMul, CheckedSub, One, Zero},
+    FixedPointNumber, FixedU128,
+};
+
+/// Curve of the loyalty multiplier which scales the claimable rewards of a deposit from
+/// `initial_reward_percentage` at the time of the deposit up to `1`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LoyaltyCurve {
+    /// `(t + initial_reward_percentage * scale_coef) / (t + scale_coef)`,
+    /// see `calculate_loyalty_multiplier`.
+    Hyperbolic {
+        initial_reward_percentage: FixedU128,
+        scale_coef: u32,
+    },
+    /// Linear vesting reaching `1` after `duration` periods.
+    Linear {
+        initial_reward_percentage: FixedU128,
+        duration: u32,
+    },
+    /// `initial_reward_percentage` until `cliff` periods have passed, then linear vesting
+    /// reaching `1` after another `duration` periods.
+    CliffLinear {
+        initial_reward_percentage: FixedU128,
+        cliff: u32,
+        duration: u32,
+    },
+    /// Exponential approach to `1` halving the remaining distance every `half_life` periods:
+    /// `1 - (1 - initial_reward_percentage) * 2^(-t / half_life)`
+    Exponential {
+        initial_reward_percentage: FixedU128,
+        half_life: u32,
+    },
+}
+
+impl LoyaltyCurve {
+    pub fn initial_reward_percentage(&self) -> FixedU128 {
+        match *self {
+            Self::Hyperbolic {
+                initial_reward_percentage,
+                ..
+            }
+            | Self::Linear {
+                initial_reward_percentage,
+                ..
+            }
+            | Self::CliffLinear {
+                initial_reward_percentage,
+                ..
+            }
+            | Self::Exponential {
+                initial_reward_percentage,
+                ..
+            } => initial_reward_percentage,
+        }
+    }
+}
+
+/// This function calculates the loyalty multiplier of `curve` after `periods` or error.
+///
+/// The multiplier is non-decreasing in `periods` and in `[initial_reward_percentage, 1]` for
+/// `initial_reward_percentage <= 1`.
+pub fn calculate_loyalty_curve_multiplier<Period: num_traits::CheckedSub + TryInto<u32> + TryInto<u128>>(
+    periods: Period,
+    curve: &LoyaltyCurve,
+) -> Result<FixedU128, MathError> {
+    let periods = TryInto::<u128>::try_into(periods).map_err(|_e| MathError::Overflow)?;
+    let initial_reward_percentage = curve.initial_reward_percentage();
+
+    match *curve {
+        LoyaltyCurve::Hyperbolic { scale_coef, .. } => {
+            if scale_coef.is_zero() {
+                return Ok(FixedU128::one());
+            }
+            calculate_loyalty_multiplier(periods, initial_reward_percentage, scale_coef)
+        }
+        LoyaltyCurve::Linear { duration, .. } => {
+            calculate_linear_multiplier(periods, initial_reward_percentage, duration)
+        }
+        LoyaltyCurve::CliffLinear { cliff, duration, .. } => match periods.checked_sub(cliff as u128) {
+            Some(vested) => calculate_linear_multiplier(vested, initial_reward_percentage, duration),
+            None => Ok(initial_reward_percentage),
+        },
+        LoyaltyCurve::Exponential { half_life, .. } => {
+            if half_life.is_zero() {
+                return Ok(FixedU128::one());
+            }
+            //1 - (1 - initial_reward_percentage) * 2^(-t / half_life)
+            let decay = calculate_decay(periods, half_life as u128)?;
+            FixedU128::one()
+                .checked_sub(&initial_reward_percentage)
+                .ok_or(Overflow)?
+                .checked_mul(&decay)
+                .and_then(|unvested| FixedU128::one().checked_sub(&unvested))
+                .ok_or(Overflow)
+        }
+    }
+}
+
+//initial_reward_percentage + (1 - initial_reward_percentage) * t / duration
+fn calculate_linear_multiplier(
+    periods: u128,
+    initial_reward_percentage: FixedU128,
+    duration: u32,
+) -> Result<FixedU128, MathError> {
+    if periods >= duration as u128 {
+        return Ok(FixedU128::one());
+    }
+
+    let vested = FixedU128::checked_from_rational(periods, duration as u128).ok_or(Overflow)?;
+    FixedU128::one()
+        .checked_sub(&initial_reward_percentage)
+        .ok_or(Overflow)?
+        .checked_mul(&vested)
+        .and_then(|vested| vested.checked_add(&initial_reward_percentage))
+        .ok_or(Overflow)
+}
+
+/// `2^(-periods / half_life)` or error. Results below `2^-64` are truncated to zero.
+fn calculate_decay(periods: u128, half_life: u128) -> Result<FixedU128, MathError> {
+    let decay: U64F64 = half_life_decay(periods, half_life).map_err(|_| Overflow)?;
+    // `decay <= 1` so the product fits into `u128`
+    Ok(FixedU128::from_inner(
+        (decay.to_bits() * FixedU128::DIV) >> U64F64::FRAC_NBITS,
+    ))
+}
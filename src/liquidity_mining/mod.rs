@@ -1,8 +1,14 @@
 #![allow(clippy::module_inception)]
 
+mod farm;
 mod liquidity_mining;
+mod loyalty_curve;
+mod projection;
 
+pub use farm::*;
 pub use liquidity_mining::*;
+pub use loyalty_curve::*;
+pub use projection::{calculate_rewards_projection, FarmParameters, RewardProjection};
 
 #[cfg(test)]
 mod invariants;
@@ -0,0 +1,130 @@
+use crate::liquidity_mining::{
+    calculate_global_farm_rewards, calculate_global_farm_shares, calculate_loyalty_curve_multiplier, LoyaltyCurve,
+};
+use crate::to_balance;
+use crate::types::Balance;
+use crate::MathError;
+use crate::MathError::{DivisionByZero, Overflow, ZeroDuration};
+
+use primitive_types::U128;
+use sp_arithmetic::{
+    traits::{checked_pow, CheckedAdd, CheckedMul, CheckedSub, One, Zero},
+    FixedPointNumber, FixedU128,
+};
+
+/// Parameters of a yield farm and its global farm used for the projection.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FarmParameters {
+    pub yield_per_period: FixedU128,
+    pub max_reward_per_period: Balance,
+    pub multiplier: FixedU128,
+    pub price_adjustment: FixedU128,
+    pub loyalty_curve: Option<LoyaltyCurve>,
+}
+
+/// Projected rewards of a deposit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RewardProjection {
+    /// Average rewards per period in the reward currency.
+    pub rewards_per_period: Balance,
+    /// Annual percentage rate, `1` is 100%.
+    pub apr: FixedU128,
+    /// Annual percentage yield with rewards compounded every period, `1` is 100%.
+    pub apy: FixedU128,
+}
+
+/// This function calculates the projected rewards of a deposit of `valued_shares` over a year of
+/// `periods_per_year` periods and returns `(projection, projection_with_loyalty)` or error.
+///
+/// `total_shares_z` are the shares of the global farm without the deposit, `remaining_rewards`
+/// is the undistributed budget of the global farm. Once the budget is exhausted no more rewards
+/// are paid, so the rates drop below the ones given by `yield_per_period`.
+///
+/// The loyalty adjusted projection assumes the rewards are claimed once at the end of the year, so
+/// they are not compounded and its `apy` equals its `apr`. It is equal to the plain projection if
+/// the farm has no loyalty curve.
+pub fn calculate_rewards_projection(
+    farm: &FarmParameters,
+    total_shares_z: Balance,
+    valued_shares: Balance,
+    remaining_rewards: Balance,
+    periods_per_year: u32,
+) -> Result<(RewardProjection, RewardProjection), MathError> {
+    if periods_per_year.is_zero() {
+        return Err(ZeroDuration);
+    }
+
+    let stake_in_global_farm = calculate_global_farm_shares(valued_shares, farm.multiplier)?;
+    let deposit_value = farm.price_adjustment.checked_mul_int(valued_shares).ok_or(Overflow)?;
+    if stake_in_global_farm.is_zero() || deposit_value.is_zero() {
+        return Err(DivisionByZero);
+    }
+
+    let total_shares_z = total_shares_z.checked_add(stake_in_global_farm).ok_or(Overflow)?;
+    let global_farm_rewards = calculate_global_farm_rewards(
+        total_shares_z,
+        farm.price_adjustment,
+        farm.yield_per_period,
+        farm.max_reward_per_period,
+        1_u32,
+    )?;
+
+    //global_farm_rewards * stake_in_global_farm / total_shares_z
+    let rewards_per_period = to_balance!(U128::from(global_farm_rewards)
+        .full_mul(stake_in_global_farm.into())
+        .checked_div(total_shares_z.into())
+        .ok_or(Overflow)?)?;
+    let rate = FixedU128::checked_from_rational(rewards_per_period, deposit_value).ok_or(Overflow)?;
+
+    // periods with full rewards and the fraction of the period in which the budget runs out
+    let (full_periods, partial_period) = match remaining_rewards.checked_div(global_farm_rewards) {
+        Some(periods) if periods < periods_per_year as u128 => (
+            periods as u32,
+            FixedU128::checked_from_rational(remaining_rewards % global_farm_rewards, global_farm_rewards)
+                .ok_or(Overflow)?,
+        ),
+        _ => (periods_per_year, FixedU128::zero()),
+    };
+    let rewarded_periods = FixedU128::from(full_periods as u128)
+        .checked_add(&partial_period)
+        .ok_or(Overflow)?;
+
+    let apr = rate.checked_mul(&rewarded_periods).ok_or(Overflow)?;
+
+    //(1 + rate)^full_periods * (1 + rate * partial_period) - 1
+    let compounded = checked_pow(
+        FixedU128::one().checked_add(&rate).ok_or(Overflow)?,
+        full_periods as usize,
+    )
+    .ok_or(Overflow)?;
+    let apy = rate
+        .checked_mul(&partial_period)
+        .and_then(|partial_rate| partial_rate.checked_add(&FixedU128::one()))
+        .and_then(|partial| compounded.checked_mul(&partial))
+        .and_then(|total| total.checked_sub(&FixedU128::one()))
+        .ok_or(Overflow)?;
+
+    let projection = RewardProjection {
+        rewards_per_period: rewarded_periods
+            .checked_mul_int(rewards_per_period)
+            .ok_or(Overflow)?
+            .checked_div(periods_per_year as u128)
+            .ok_or(Overflow)?,
+        apr,
+        apy,
+    };
+
+    let loyalty_multiplier = match farm.loyalty_curve {
+        Some(curve) => calculate_loyalty_curve_multiplier(periods_per_year, &curve)?,
+        None => return Ok((projection, projection)),
+    };
+    let projection_with_loyalty = RewardProjection {
+        rewards_per_period: loyalty_multiplier
+            .checked_mul_int(projection.rewards_per_period)
+            .ok_or(Overflow)?,
+        apr: loyalty_multiplier.checked_mul(&projection.apr).ok_or(Overflow)?,
+        apy: loyalty_multiplier.checked_mul(&projection.apr).ok_or(Overflow)?,
+    };
+
+    Ok((projection, projection_with_loyalty))
+}
@@ -1,10 +1,16 @@
 use crate::liquidity_mining::liquidity_mining::*;
+use crate::liquidity_mining::{
+    calculate_global_farm_rewards_per_currency, calculate_loyalty_curve_multiplier, calculate_rewards_projection,
+    FarmParameters, GlobalFarm, LoyaltyCurve, RewardCurrency, RewardProjection, YieldFarm,
+};
+use crate::types::BASILISK_ONE as ONE;
 
-use sp_arithmetic::FixedU128;
+use sp_arithmetic::{FixedPointNumber, FixedU128};
 
 use std::vec;
 
 use crate::assert_approx_eq;
+use crate::MathError;
 
 #[test]
 fn calculate_loyalty_multiplier_should_work() {
@@ -954,3 +960,514 @@ fn calculate_global_farm_rewards_should_work() {
         );
     }
 }
+
+#[test]
+fn farm_simulation_should_distribute_rewards() {
+    let budget = 1_000_000 * ONE;
+    let mut global_farm = GlobalFarm::new(
+        [RewardCurrency::new(
+            budget,
+            FixedU128::from_rational(1, 100),
+            1_000 * ONE,
+            FixedU128::from(1),
+        )],
+        0_u32,
+    );
+    let mut yield_farm = YieldFarm::new(&global_farm, FixedU128::from(1), None, 0_u32);
+
+    let mut deposit = yield_farm.deposit(&mut global_farm, 100 * ONE, 1, 0).unwrap();
+    assert_eq!(deposit.valued_shares, 100 * ONE);
+    assert_eq!(global_farm.total_shares_z, 100 * ONE);
+
+    // 100 * 1% * 10 periods
+    assert_eq!(
+        yield_farm.claim(&mut global_farm, &mut deposit, 10).unwrap(),
+        ([10 * ONE], [0])
+    );
+    assert_eq!(
+        global_farm.reward_currencies[0].accumulated_rpz,
+        FixedU128::from_rational(1, 10)
+    );
+    assert_eq!(yield_farm.accumulated_rpvs[0], FixedU128::from_rational(1, 10));
+    assert_eq!(global_farm.reward_currencies[0].paid_rewards, 10 * ONE);
+    // nothing left to claim in the same period
+    assert_eq!(
+        yield_farm.claim(&mut global_farm, &mut deposit, 10).unwrap(),
+        ([0], [0])
+    );
+
+    // second depositor shares the rewards of later periods
+    let second = yield_farm.deposit(&mut global_farm, 300 * ONE, 1, 10).unwrap();
+    assert_eq!(
+        yield_farm.withdraw(&mut global_farm, second, 20).unwrap(),
+        ([30 * ONE], [0])
+    );
+    assert_eq!(
+        yield_farm.claim(&mut global_farm, &mut deposit, 20).unwrap(),
+        ([10 * ONE], [0])
+    );
+    assert_eq!(yield_farm.total_shares, 100 * ONE);
+    assert_eq!(global_farm.total_shares_z, 100 * ONE);
+
+    assert_eq!(global_farm.total_rewards(&[yield_farm]).unwrap(), [budget]);
+}
+
+#[test]
+fn farm_simulation_should_not_exceed_budget() {
+    let budget = 15 * ONE;
+    let mut global_farm = GlobalFarm::new(
+        [RewardCurrency::new(
+            budget,
+            FixedU128::from_rational(1, 100),
+            1_000 * ONE,
+            FixedU128::from(1),
+        )],
+        0_u32,
+    );
+    let mut yield_farm = YieldFarm::new(&global_farm, FixedU128::from(1), None, 0_u32);
+    let mut deposit = yield_farm.deposit(&mut global_farm, 100 * ONE, 1, 0).unwrap();
+
+    assert_eq!(
+        yield_farm.claim(&mut global_farm, &mut deposit, 10).unwrap(),
+        ([10 * ONE], [0])
+    );
+    // only 5 left in the budget
+    assert_eq!(
+        yield_farm.claim(&mut global_farm, &mut deposit, 20).unwrap(),
+        ([5 * ONE], [0])
+    );
+    assert_eq!(
+        yield_farm.claim(&mut global_farm, &mut deposit, 30).unwrap(),
+        ([0], [0])
+    );
+    assert_eq!(global_farm.reward_currencies[0].remaining_rewards, 0);
+    assert_eq!(global_farm.reward_currencies[0].paid_rewards, budget);
+}
+
+#[test]
+fn farm_simulation_should_return_unclaimable_rewards_on_withdraw() {
+    let budget = 1_000_000 * ONE;
+    let mut global_farm = GlobalFarm::new(
+        [RewardCurrency::new(
+            budget,
+            FixedU128::from_rational(1, 100),
+            1_000 * ONE,
+            FixedU128::from(1),
+        )],
+        0_u32,
+    );
+    let loyalty_curve = LoyaltyCurve::Hyperbolic {
+        initial_reward_percentage: FixedU128::from_rational(1, 2),
+        scale_coef: 100,
+    };
+    let mut yield_farm = YieldFarm::new(&global_farm, FixedU128::from(1), Some(loyalty_curve), 0_u32);
+    let deposit = yield_farm.deposit(&mut global_farm, 100 * ONE, 1, 0).unwrap();
+
+    let ([claimed], [unclaimable]) = yield_farm.withdraw(&mut global_farm, deposit, 10).unwrap();
+    let loyalty_multiplier = calculate_loyalty_multiplier(10_u32, FixedU128::from_rational(1, 2), 100).unwrap();
+    assert_eq!(claimed, loyalty_multiplier.checked_mul_int(10 * ONE).unwrap());
+    assert_eq!(unclaimable, 10 * ONE - claimed);
+    assert!(unclaimable > 0);
+    assert_eq!(
+        global_farm.reward_currencies[0].remaining_rewards,
+        budget - 10 * ONE + unclaimable
+    );
+    assert_eq!(yield_farm.total_shares, 0);
+    assert_eq!(global_farm.total_shares_z, 0);
+    assert_eq!(global_farm.total_rewards(&[yield_farm]).unwrap(), [budget]);
+}
+
+#[test]
+fn farm_simulation_should_claim_rewards_of_deposit_stakes() {
+    let budget = 1_000_000 * ONE;
+    let mut global_farm = GlobalFarm::new(
+        [RewardCurrency::new(
+            budget,
+            FixedU128::from(1),
+            1_000 * ONE,
+            FixedU128::from(1),
+        )],
+        0_u32,
+    );
+    let mut yield_farm = YieldFarm::new(&global_farm, FixedU128::from_rational(3, 2), None, 0_u32);
+
+    // stake of each deposit is rounded down to 1, while 1.5 * total valued shares is 3
+    let mut first = yield_farm.deposit(&mut global_farm, 1, 1, 0).unwrap();
+    let second = yield_farm.deposit(&mut global_farm, 1, 1, 0).unwrap();
+    assert_eq!(first.stake_in_global_farm, 1);
+    assert_eq!(yield_farm.total_stake_in_global_farm, 2);
+    assert_eq!(global_farm.total_shares_z, 2);
+
+    // 2 * 100% * 10 periods
+    assert_eq!(yield_farm.claim(&mut global_farm, &mut first, 10).unwrap(), ([10], [0]));
+    assert_eq!(global_farm.reward_currencies[0].pending_rewards, 0);
+    assert_eq!(yield_farm.withdraw(&mut global_farm, second, 10).unwrap(), ([10], [0]));
+    assert_eq!(global_farm.total_rewards(&[yield_farm]).unwrap(), [budget]);
+}
+
+#[test]
+fn farm_simulation_should_withdraw_stake_of_deposit_after_multiplier_change() {
+    let budget = 1_000_000 * ONE;
+    let mut global_farm = GlobalFarm::new(
+        [RewardCurrency::new(
+            budget,
+            FixedU128::from_rational(1, 100),
+            1_000 * ONE,
+            FixedU128::from(1),
+        )],
+        0_u32,
+    );
+    let mut yield_farm = YieldFarm::new(&global_farm, FixedU128::from(1), None, 0_u32);
+    let first = yield_farm.deposit(&mut global_farm, 100 * ONE, 1, 0).unwrap();
+
+    yield_farm.multiplier = FixedU128::from(2);
+    let second = yield_farm.deposit(&mut global_farm, 100 * ONE, 1, 0).unwrap();
+    assert_eq!(first.stake_in_global_farm, 100 * ONE);
+    assert_eq!(second.stake_in_global_farm, 200 * ONE);
+    assert_eq!(global_farm.total_shares_z, 300 * ONE);
+
+    // 300 * 1% * 10 periods shared by the valued shares of the deposits
+    assert_eq!(
+        yield_farm.withdraw(&mut global_farm, first, 10).unwrap(),
+        ([15 * ONE], [0])
+    );
+    assert_eq!(global_farm.total_shares_z, 200 * ONE);
+    assert_eq!(
+        yield_farm.withdraw(&mut global_farm, second, 10).unwrap(),
+        ([15 * ONE], [0])
+    );
+    assert_eq!(global_farm.total_shares_z, 0);
+    assert_eq!(yield_farm.total_stake_in_global_farm, 0);
+    assert_eq!(global_farm.total_rewards(&[yield_farm]).unwrap(), [budget]);
+}
+
+#[test]
+fn calculate_rewards_projection_should_work() {
+    let farm = FarmParameters {
+        yield_per_period: FixedU128::from_rational(1, 1_000),
+        max_reward_per_period: 1_000 * ONE,
+        multiplier: FixedU128::from(1),
+        price_adjustment: FixedU128::from(1),
+        loyalty_curve: None,
+    };
+
+    // deposit gets 10% of 1 ONE rewarded per period
+    let (projection, projection_with_loyalty) =
+        calculate_rewards_projection(&farm, 900 * ONE, 100 * ONE, 1_000_000 * ONE, 10).unwrap();
+    assert_eq!(projection.rewards_per_period, ONE / 10);
+    assert_eq!(projection.apr, FixedU128::from_rational(1, 100));
+    //1.001^10 - 1
+    assert_approx_eq!(
+        projection.apy,
+        FixedU128::from_inner(10_045_120_210_252_210),
+        FixedU128::from_inner(100)
+    );
+    assert_eq!(projection_with_loyalty, projection);
+
+    // rewards of the global farm are capped
+    let capped_farm = FarmParameters {
+        max_reward_per_period: ONE / 2,
+        ..farm
+    };
+    let (projection, _) =
+        calculate_rewards_projection(&capped_farm, 900 * ONE, 100 * ONE, 1_000_000 * ONE, 10).unwrap();
+    assert_eq!(projection.rewards_per_period, ONE / 20);
+    assert_eq!(projection.apr, FixedU128::from_rational(5, 1_000));
+}
+
+#[test]
+fn calculate_rewards_projection_should_account_for_budget_exhaustion() {
+    let farm = FarmParameters {
+        yield_per_period: FixedU128::from_rational(1, 1_000),
+        max_reward_per_period: 1_000 * ONE,
+        multiplier: FixedU128::from(1),
+        price_adjustment: FixedU128::from(1),
+        loyalty_curve: None,
+    };
+
+    // budget runs out after 2.5 of 10 periods
+    let (projection, _) = calculate_rewards_projection(&farm, 900 * ONE, 100 * ONE, 5 * ONE / 2, 10).unwrap();
+    assert_eq!(
+        projection,
+        RewardProjection {
+            rewards_per_period: ONE / 40,
+            apr: FixedU128::from_rational(25, 10_000),
+            //1.001^2 * 1.0005 - 1
+            apy: FixedU128::from_inner(2_502_000_500_000_000),
+        }
+    );
+
+    let (projection, _) = calculate_rewards_projection(&farm, 900 * ONE, 100 * ONE, 0, 10).unwrap();
+    assert_eq!(
+        projection,
+        RewardProjection {
+            rewards_per_period: 0,
+            apr: FixedU128::from(0),
+            apy: FixedU128::from(0),
+        }
+    );
+}
+
+#[test]
+fn calculate_rewards_projection_should_apply_loyalty_curve() {
+    let farm = FarmParameters {
+        yield_per_period: FixedU128::from_rational(1, 1_000),
+        max_reward_per_period: 1_000 * ONE,
+        multiplier: FixedU128::from(2),
+        price_adjustment: FixedU128::from_rational(1, 2),
+        loyalty_curve: Some(LoyaltyCurve::Hyperbolic {
+            initial_reward_percentage: FixedU128::from_rational(1, 2),
+            scale_coef: 10,
+        }),
+    };
+
+    let (projection, projection_with_loyalty) =
+        calculate_rewards_projection(&farm, 0, 100 * ONE, 1_000_000 * ONE, 10).unwrap();
+    // the only deposit gets all rewards: 0.5 * 200 * 0.001
+    assert_eq!(projection.rewards_per_period, ONE / 10);
+    assert_eq!(projection.apr, FixedU128::from_rational(2, 100));
+
+    // loyalty multiplier after 10 periods: (10 + 5) / (10 + 10) = 3/4
+    assert_eq!(
+        projection_with_loyalty,
+        RewardProjection {
+            rewards_per_period: 3 * ONE / 40,
+            apr: FixedU128::from_rational(15, 1_000),
+            // rewards claimed once at the end of the year are not compounded
+            apy: FixedU128::from_rational(15, 1_000),
+        }
+    );
+}
+
+#[test]
+fn calculate_rewards_projection_should_fail_for_invalid_input() {
+    let farm = FarmParameters {
+        yield_per_period: FixedU128::from_rational(1, 1_000),
+        max_reward_per_period: 1_000 * ONE,
+        multiplier: FixedU128::from(1),
+        price_adjustment: FixedU128::from(1),
+        loyalty_curve: None,
+    };
+
+    assert_eq!(
+        calculate_rewards_projection(&farm, 900 * ONE, 100 * ONE, ONE, 0),
+        Err(MathError::ZeroDuration)
+    );
+    assert_eq!(
+        calculate_rewards_projection(&farm, 900 * ONE, 0, ONE, 10),
+        Err(MathError::DivisionByZero)
+    );
+}
+
+#[test]
+fn calculate_loyalty_curve_multiplier_should_work() {
+    let initial_reward_percentage = FixedU128::from_rational(1, 2);
+
+    let hyperbolic = LoyaltyCurve::Hyperbolic {
+        initial_reward_percentage,
+        scale_coef: 100,
+    };
+    for periods in [0_u32, 1, 10, 100, 1_000_000] {
+        assert_eq!(
+            calculate_loyalty_curve_multiplier(periods, &hyperbolic).unwrap(),
+            calculate_loyalty_multiplier(periods, initial_reward_percentage, 100).unwrap()
+        );
+    }
+
+    let linear = LoyaltyCurve::Linear {
+        initial_reward_percentage,
+        duration: 100,
+    };
+    let expected = [
+        (0_u32, initial_reward_percentage),
+        (25, FixedU128::from_rational(5, 8)),
+        (50, FixedU128::from_rational(3, 4)),
+        (100, FixedU128::from(1)),
+        (1_000, FixedU128::from(1)),
+    ];
+    for (periods, multiplier) in expected {
+        assert_eq!(
+            calculate_loyalty_curve_multiplier(periods, &linear).unwrap(),
+            multiplier
+        );
+    }
+
+    let cliff_linear = LoyaltyCurve::CliffLinear {
+        initial_reward_percentage,
+        cliff: 50,
+        duration: 100,
+    };
+    let expected = [
+        (0_u32, initial_reward_percentage),
+        (49, initial_reward_percentage),
+        (50, initial_reward_percentage),
+        (100, FixedU128::from_rational(3, 4)),
+        (150, FixedU128::from(1)),
+    ];
+    for (periods, multiplier) in expected {
+        assert_eq!(
+            calculate_loyalty_curve_multiplier(periods, &cliff_linear).unwrap(),
+            multiplier
+        );
+    }
+
+    let exponential = LoyaltyCurve::Exponential {
+        initial_reward_percentage,
+        half_life: 10,
+    };
+    let expected = [
+        (0_u32, initial_reward_percentage),
+        (10, FixedU128::from_rational(3, 4)),
+        (20, FixedU128::from_rational(7, 8)),
+        (10_000, FixedU128::from(1)),
+    ];
+    for (periods, multiplier) in expected {
+        assert_eq!(
+            calculate_loyalty_curve_multiplier(periods, &exponential).unwrap(),
+            multiplier
+        );
+    }
+    //1 - 0.5 * 2^(-1/2)
+    assert_approx_eq!(
+        calculate_loyalty_curve_multiplier(5_u32, &exponential).unwrap(),
+        FixedU128::from_inner(646_446_609_406_726_238),
+        FixedU128::from_inner(1_000)
+    );
+
+    // vesting is immediate for zero durations
+    let immediate = LoyaltyCurve::Hyperbolic {
+        initial_reward_percentage,
+        scale_coef: 0,
+    };
+    assert_eq!(
+        calculate_loyalty_curve_multiplier(0_u32, &immediate).unwrap(),
+        FixedU128::from(1)
+    );
+    let immediate = LoyaltyCurve::Linear {
+        initial_reward_percentage,
+        duration: 0,
+    };
+    assert_eq!(
+        calculate_loyalty_curve_multiplier(0_u32, &immediate).unwrap(),
+        FixedU128::from(1)
+    );
+}
+
+#[test]
+fn calculate_global_farm_rewards_per_currency_should_work() {
+    let reward_currencies = [
+        RewardCurrency::new(
+            1_000_000 * ONE,
+            FixedU128::from_rational(1, 100),
+            1_000 * ONE,
+            FixedU128::from(1),
+        ),
+        RewardCurrency::new(
+            ONE / 2,
+            FixedU128::from_rational(1, 1_000),
+            1_000 * ONE,
+            FixedU128::from(1),
+        ),
+        RewardCurrency::new(
+            1_000_000 * ONE,
+            FixedU128::from_rational(1, 100),
+            ONE,
+            FixedU128::from(2),
+        ),
+    ];
+
+    assert_eq!(
+        calculate_global_farm_rewards_per_currency(100 * ONE, &reward_currencies, 10_u32).unwrap(),
+        // second capped by the budget, third by the max reward per period
+        [10 * ONE, ONE / 2, 10 * ONE]
+    );
+}
+
+#[test]
+fn multi_reward_farm_simulation_should_distribute_every_currency() {
+    let budgets = [1_000_000 * ONE, ONE / 2];
+    let mut global_farm = GlobalFarm::new(
+        [
+            RewardCurrency::new(
+                budgets[0],
+                FixedU128::from_rational(1, 100),
+                1_000 * ONE,
+                FixedU128::from(1),
+            ),
+            RewardCurrency::new(
+                budgets[1],
+                FixedU128::from_rational(1, 1_000),
+                1_000 * ONE,
+                FixedU128::from(1),
+            ),
+        ],
+        0_u32,
+    );
+    let mut yield_farm = YieldFarm::new(&global_farm, FixedU128::from(1), None, 0_u32);
+
+    let mut deposit = yield_farm.deposit(&mut global_farm, 100 * ONE, 1, 0).unwrap();
+    assert_eq!(
+        yield_farm.claim(&mut global_farm, &mut deposit, 10).unwrap(),
+        ([10 * ONE, ONE / 2], [0, 0])
+    );
+    assert_eq!(
+        global_farm.reward_currencies[0].accumulated_rpz,
+        FixedU128::from_rational(1, 10)
+    );
+    assert_eq!(
+        global_farm.reward_currencies[1].accumulated_rpz,
+        FixedU128::from_rational(1, 200)
+    );
+
+    // second currency is exhausted
+    assert_eq!(
+        yield_farm.claim(&mut global_farm, &mut deposit, 20).unwrap(),
+        ([10 * ONE, 0], [0, 0])
+    );
+    assert_eq!(global_farm.reward_currencies[1].paid_rewards, budgets[1]);
+    assert_eq!(global_farm.total_rewards(&[yield_farm]).unwrap(), budgets);
+}
+
+#[test]
+fn multi_reward_farm_simulation_should_return_unclaimable_rewards_per_currency() {
+    let budgets = [1_000_000 * ONE, 1_000_000 * ONE];
+    let mut global_farm = GlobalFarm::new(
+        [
+            RewardCurrency::new(
+                budgets[0],
+                FixedU128::from_rational(1, 100),
+                1_000 * ONE,
+                FixedU128::from(1),
+            ),
+            RewardCurrency::new(
+                budgets[1],
+                FixedU128::from_rational(1, 1_000),
+                1_000 * ONE,
+                FixedU128::from(1),
+            ),
+        ],
+        0_u32,
+    );
+    let loyalty_curve = LoyaltyCurve::Linear {
+        initial_reward_percentage: FixedU128::from_rational(1, 2),
+        duration: 20,
+    };
+    let mut yield_farm = YieldFarm::new(&global_farm, FixedU128::from(1), Some(loyalty_curve), 0_u32);
+    let deposit = yield_farm.deposit(&mut global_farm, 100 * ONE, 1, 0).unwrap();
+
+    // loyalty multiplier is 3/4 after 10 of 20 periods
+    assert_eq!(
+        yield_farm.withdraw(&mut global_farm, deposit, 10).unwrap(),
+        ([15 * ONE / 2, 3 * ONE / 4], [5 * ONE / 2, ONE / 4])
+    );
+    assert_eq!(
+        global_farm.reward_currencies[0].remaining_rewards,
+        budgets[0] - 10 * ONE + 5 * ONE / 2
+    );
+    assert_eq!(
+        global_farm.reward_currencies[1].remaining_rewards,
+        budgets[1] - ONE + ONE / 4
+    );
+    assert_eq!(global_farm.total_shares_z, 0);
+    assert_eq!(global_farm.total_rewards(&[yield_farm]).unwrap(), budgets);
+}
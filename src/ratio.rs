@@ -1,10 +1,13 @@
+use crate::support::rational::Rounding;
+use crate::types::Balance;
 use codec::{Decode, Encode, MaxEncodedLen};
 use core::cmp::{Ord, Ordering, PartialOrd};
 use num_traits::Zero;
+use primitive_types::U512;
 use scale_info::TypeInfo;
 #[cfg(feature = "std")]
 use serde::{Deserialize, Serialize};
-use sp_arithmetic::helpers_128bit;
+use sp_arithmetic::{helpers_128bit, FixedPointNumber, FixedU128};
 
 /// A rational number represented by a `n`umerator and `d`enominator.
 #[derive(Clone, Copy, Default, PartialEq, Eq, Encode, Decode, TypeInfo, MaxEncodedLen)]
@@ -42,6 +45,225 @@ impl Ratio {
     pub const fn is_zero(&self) -> bool {
         self.n == 0
     }
+
+    /// Build from a raw `n/d` reduced to the canonical form, see `reduce`.
+    pub fn canonical(n: u128, d: u128) -> Self {
+        Self::new(n, d).reduce()
+    }
+
+    /// Divide numerator and denominator by their greatest common divisor.
+    /// Zero is reduced to `0/1`, so equal values have the same encoding.
+    pub fn reduce(&self) -> Self {
+        if self.d.is_zero() {
+            return *self;
+        }
+        if self.n.is_zero() {
+            return Self::zero();
+        }
+        let gcd = binary_gcd(self.n, self.d);
+        Self::new_unchecked(self.n / gcd, self.d / gcd)
+    }
+
+    /// Largest representable value.
+    pub const fn max_value() -> Self {
+        Self::new_unchecked(u128::MAX, 1)
+    }
+
+    /// Add `other` to `self`, rounding the result according to `rounding`.
+    /// Returns `None` if the result is not representable.
+    pub fn checked_add(&self, other: &Self, rounding: Rounding) -> Option<Self> {
+        let (l_n, l_d, r_n, r_d) = self.widen(other);
+        // n = l.n * r.d + r.n * l.d
+        let n = l_n.checked_mul(r_d)?.checked_add(r_n.checked_mul(l_d)?)?;
+        // d = l.d * r.d
+        let d = l_d.checked_mul(r_d)?;
+        Self::from_wide(n, d, rounding).map(Self::normalized)
+    }
+
+    /// Subtract `other` from `self`, rounding the result according to `rounding`.
+    /// Returns `None` if `other` is greater than `self` or the result is not representable.
+    pub fn checked_sub(&self, other: &Self, rounding: Rounding) -> Option<Self> {
+        let (l_n, l_d, r_n, r_d) = self.widen(other);
+        // n = l.n * r.d - r.n * l.d
+        let n = l_n.checked_mul(r_d)?.checked_sub(r_n.checked_mul(l_d)?)?;
+        // d = l.d * r.d
+        let d = l_d.checked_mul(r_d)?;
+        Self::from_wide(n, d, rounding).map(Self::normalized)
+    }
+
+    /// Multiply `self` by `other`, rounding the result according to `rounding`.
+    /// Returns `None` if the result is not representable.
+    pub fn checked_mul(&self, other: &Self, rounding: Rounding) -> Option<Self> {
+        let (l_n, l_d, r_n, r_d) = self.widen(other);
+        Self::from_wide(l_n.checked_mul(r_n)?, l_d.checked_mul(r_d)?, rounding).map(Self::normalized)
+    }
+
+    /// Divide `self` by `other`, rounding the result according to `rounding`.
+    /// Returns `None` if `other` is zero or the result is not representable.
+    pub fn checked_div(&self, other: &Self, rounding: Rounding) -> Option<Self> {
+        let (l_n, l_d, r_n, r_d) = self.widen(other);
+        Self::from_wide(l_n.checked_mul(r_d)?, l_d.checked_mul(r_n)?, rounding).map(Self::normalized)
+    }
+
+    /// Add `other` to `self`, saturating at the largest representable value.
+    pub fn saturating_add(&self, other: &Self, rounding: Rounding) -> Self {
+        self.checked_add(other, rounding).unwrap_or_else(Self::max_value)
+    }
+
+    /// Subtract `other` from `self`, saturating at zero.
+    pub fn saturating_sub(&self, other: &Self, rounding: Rounding) -> Self {
+        if other >= self {
+            return Self::zero();
+        }
+        // Only an invalid denominator makes the subtraction fail
+        self.checked_sub(other, rounding).unwrap_or_else(Self::zero)
+    }
+
+    /// Multiply `self` by `other`, saturating at the largest representable value.
+    pub fn saturating_mul(&self, other: &Self, rounding: Rounding) -> Self {
+        self.checked_mul(other, rounding).unwrap_or_else(Self::max_value)
+    }
+
+    /// Divide `self` by `other`, saturating at the largest representable value.
+    /// Division by zero results in the largest representable value.
+    pub fn saturating_div(&self, other: &Self, rounding: Rounding) -> Self {
+        self.checked_div(other, rounding).unwrap_or_else(Self::max_value)
+    }
+
+    /// Exact reciprocal of `self`. Returns `None` if `self` is zero.
+    pub fn reciprocal(&self) -> Option<Self> {
+        if self.n.is_zero() || self.d.is_zero() {
+            return None;
+        }
+        Some(Self::new_unchecked(self.d, self.n))
+    }
+
+    /// Convert to `FixedU128`, rounding according to `rounding`.
+    /// Returns `None` if the value is not representable.
+    pub fn to_fixed_u128(&self, rounding: Rounding) -> Option<FixedU128> {
+        let inner = self.mul_balance(FixedU128::DIV, rounding)?;
+        Some(FixedU128::from_inner(inner))
+    }
+
+    /// Exact conversion from `FixedU128`.
+    pub fn from_fixed_u128(value: FixedU128) -> Self {
+        Self::new(value.into_inner(), FixedU128::DIV)
+    }
+
+    /// Multiply `amount` by `self`, rounding according to `rounding`.
+    /// Returns `None` if the denominator is zero or the result does not fit into `Balance`.
+    pub fn mul_balance(&self, amount: Balance, rounding: Rounding) -> Option<Balance> {
+        if self.d.is_zero() {
+            return None;
+        }
+        let (amount, n, d) = (U512::from(amount), U512::from(self.n), U512::from(self.d));
+        let product = amount.checked_mul(n)?;
+        let (q, r) = product.div_mod(d);
+        let q = match rounding {
+            Rounding::Down => q,
+            Rounding::Up if r.is_zero() => q,
+            Rounding::Up => q.checked_add(U512::one())?,
+            Rounding::Nearest if (r << 1) >= d => q.checked_add(U512::one())?,
+            Rounding::Nearest => q,
+        };
+        Balance::try_from(q).ok()
+    }
+
+    /// Results of arithmetic are reduced if the `reduced-ratio` feature is enabled.
+    fn normalized(self) -> Self {
+        if cfg!(feature = "reduced-ratio") {
+            self.reduce()
+        } else {
+            self
+        }
+    }
+
+    fn widen(&self, other: &Self) -> (U512, U512, U512, U512) {
+        (
+            U512::from(self.n),
+            U512::from(self.d),
+            U512::from(other.n),
+            U512::from(other.d),
+        )
+    }
+
+    /// Round a 512 bit rational number to a 128 bit rational number.
+    /// `Down` and `Up` ensure the result is less or equal and greater or equal to the input respectively.
+    /// `Nearest` rounds both numerator and denominator down.
+    fn from_wide(n: U512, d: U512, rounding: Rounding) -> Option<Self> {
+        if d.is_zero() {
+            return None;
+        }
+        if n.is_zero() {
+            return Some(Self::zero());
+        }
+
+        let (q, r) = n.div_mod(d);
+        let max = U512::from(u128::MAX);
+        if q > max || (q == max && !r.is_zero() && rounding == Rounding::Up) {
+            return None;
+        }
+
+        let (n_bits, d_bits) = (n.bits(), d.bits());
+        if n_bits.max(d_bits) <= 128 {
+            return Some(Self::new_unchecked(n.low_u128(), d.low_u128()));
+        }
+
+        // One spare bit guarantees that rounding up after shifting fits into u128
+        let shift = n_bits.max(d_bits) - 127;
+
+        // Large values have more precision as an integer
+        if (d >> shift).is_zero() || n_bits.saturating_sub(d_bits) > 64 {
+            let q = match rounding {
+                Rounding::Down => q,
+                Rounding::Up if r.is_zero() => q,
+                Rounding::Up => q + U512::one(),
+                Rounding::Nearest if (r << 1) >= d => q + U512::one(),
+                Rounding::Nearest => q,
+            };
+            return (q <= max).then(|| Self::new_unchecked(q.low_u128(), 1));
+        }
+
+        let shift_up = |x: U512| {
+            let shifted = x >> shift;
+            if (shifted << shift) == x {
+                shifted
+            } else {
+                shifted + U512::one()
+            }
+        };
+
+        let (n, d) = match rounding {
+            Rounding::Down => (n >> shift, shift_up(d)),
+            Rounding::Up => (shift_up(n), d >> shift),
+            Rounding::Nearest => ((n >> shift).max(U512::one()), d >> shift),
+        };
+        Some(Self::new_unchecked(n.low_u128(), d.low_u128()))
+    }
+}
+
+/// Greatest common divisor of `a` and `b` using Stein's algorithm.
+fn binary_gcd(mut a: u128, mut b: u128) -> u128 {
+    if a.is_zero() {
+        return b;
+    }
+    if b.is_zero() {
+        return a;
+    }
+
+    let shift = (a | b).trailing_zeros();
+    a >>= a.trailing_zeros();
+    loop {
+        b >>= b.trailing_zeros();
+        if a > b {
+            core::mem::swap(&mut a, &mut b);
+        }
+        // Bare math is safe because a <= b
+        b -= a;
+        if b.is_zero() {
+            return a << shift;
+        }
+    }
 }
 
 impl From<Ratio> for (u128, u128) {
@@ -111,3 +333,189 @@ impl sp_std::fmt::Debug for Ratio {
         write!(f, "Ratio({} / {})", self.n, self.d)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use rug::{Integer, Rational};
+
+    #[test]
+    fn arithmetic_should_work() {
+        let half = Ratio::new(1, 2);
+        let third = Ratio::new(1, 3);
+
+        assert_eq!(half.checked_add(&third, Rounding::Down), Some(Ratio::new(5, 6)));
+        assert_eq!(half.checked_sub(&third, Rounding::Down), Some(Ratio::new(1, 6)));
+        assert_eq!(third.checked_sub(&half, Rounding::Down), None);
+        assert_eq!(half.checked_mul(&third, Rounding::Down), Some(Ratio::new(1, 6)));
+        assert_eq!(half.checked_div(&third, Rounding::Down), Some(Ratio::new(3, 2)));
+        assert_eq!(half.checked_div(&Ratio::zero(), Rounding::Down), None);
+        assert_eq!(
+            Ratio::zero().checked_add(&Ratio::zero(), Rounding::Up),
+            Some(Ratio::zero())
+        );
+
+        let max = Ratio::max_value();
+        assert_eq!(max.checked_add(&half, Rounding::Down), None);
+        assert_eq!(max.checked_mul(&Ratio::new(2, 1), Rounding::Down), None);
+        assert_eq!(max.saturating_add(&half, Rounding::Down), max);
+        assert_eq!(max.saturating_mul(&Ratio::new(2, 1), Rounding::Down), max);
+        assert_eq!(third.saturating_sub(&half, Rounding::Down), Ratio::zero());
+        assert_eq!(half.saturating_div(&Ratio::zero(), Rounding::Down), max);
+
+        assert_eq!(Ratio::new(2, 3).reciprocal(), Some(Ratio::new(3, 2)));
+        assert_eq!(Ratio::zero().reciprocal(), None);
+    }
+
+    #[test]
+    fn rounding_should_be_directional() {
+        let a = Ratio::new(u128::MAX, u128::MAX - 1);
+        let b = Ratio::new(u128::MAX - 2, u128::MAX);
+        let exact = Rational::from(a) * Rational::from(b);
+
+        let down = a.checked_mul(&b, Rounding::Down).unwrap();
+        let up = a.checked_mul(&b, Rounding::Up).unwrap();
+        assert!(Rational::from(down) <= exact);
+        assert!(Rational::from(up) >= exact);
+        assert!(down < up);
+    }
+
+    #[test]
+    fn conversions_should_work() {
+        let third = Ratio::new(1, 3);
+        assert_eq!(
+            third.to_fixed_u128(Rounding::Down),
+            Some(FixedU128::from_inner(333_333_333_333_333_333))
+        );
+        assert_eq!(
+            third.to_fixed_u128(Rounding::Up),
+            Some(FixedU128::from_inner(333_333_333_333_333_334))
+        );
+        assert_eq!(Ratio::max_value().to_fixed_u128(Rounding::Down), None);
+        assert_eq!(
+            Ratio::from_fixed_u128(FixedU128::from_rational(1, 4)),
+            Ratio::new(250_000_000_000_000_000, 1_000_000_000_000_000_000)
+        );
+
+        assert_eq!(third.mul_balance(100, Rounding::Down), Some(33));
+        assert_eq!(third.mul_balance(100, Rounding::Up), Some(34));
+        assert_eq!(Ratio::new(2, 3).mul_balance(100, Rounding::Nearest), Some(67));
+        assert_eq!(third.mul_balance(100, Rounding::Nearest), Some(33));
+        assert_eq!(Ratio::new(2, 1).mul_balance(u128::MAX, Rounding::Down), None);
+        assert_eq!(Ratio::new_unchecked(1, 0).mul_balance(1, Rounding::Down), None);
+    }
+
+    #[test]
+    fn reduce_should_work() {
+        assert_eq!(Ratio::new(2, 4).reduce(), Ratio::new(1, 2));
+        assert_eq!(Ratio::new(0, 4).reduce(), Ratio::zero());
+        assert_eq!(Ratio::new(7, 13).reduce(), Ratio::new(7, 13));
+        assert_eq!(Ratio::new(u128::MAX, u128::MAX).reduce(), Ratio::one());
+        assert_eq!(Ratio::new(1 << 100, 1 << 90).reduce(), Ratio::new(1 << 10, 1));
+        assert_eq!(Ratio::new_unchecked(5, 0).reduce(), Ratio::new_unchecked(5, 0));
+        assert_eq!(Ratio::canonical(6, 9), Ratio::new(2, 3));
+        assert_eq!(Ratio::canonical(2, 4).encode(), Ratio::canonical(1, 2).encode());
+    }
+
+    #[test]
+    fn binary_gcd_should_work() {
+        assert_eq!(binary_gcd(0, 0), 0);
+        assert_eq!(binary_gcd(0, 5), 5);
+        assert_eq!(binary_gcd(12, 18), 6);
+        assert_eq!(binary_gcd(17, 5), 1);
+        assert_eq!(binary_gcd(u128::MAX, u128::MAX), u128::MAX);
+        assert_eq!(binary_gcd(1 << 127, 1 << 64), 1 << 64);
+    }
+
+    #[cfg(feature = "reduced-ratio")]
+    #[test]
+    fn arithmetic_results_should_be_reduced() {
+        let half = Ratio::new(1, 2);
+        assert_eq!(half.checked_add(&half, Rounding::Down), Some(Ratio::one()));
+        assert_eq!(
+            Ratio::new(2, 3).checked_mul(&Ratio::new(3, 4), Rounding::Down),
+            Some(half)
+        );
+    }
+
+    fn ratio() -> impl Strategy<Value = Ratio> {
+        (any::<u128>(), 1..u128::MAX, 1..=128u32, 1..=128u32).prop_map(|(n, d, n_bits, d_bits)| {
+            // vary magnitudes of numerator and denominator
+            Ratio::new(n >> (128 - n_bits), (d >> (128 - d_bits)).max(1))
+        })
+    }
+
+    fn rounding() -> impl Strategy<Value = Rounding> {
+        prop_oneof![Just(Rounding::Down), Just(Rounding::Up), Just(Rounding::Nearest)]
+    }
+
+    /// Check that `result` is rounded in the right direction and is close to `exact`.
+    fn assert_rounded(result: Option<Ratio>, exact: Rational, rounding: Rounding) -> Result<(), TestCaseError> {
+        let max = Rational::from(u128::MAX);
+        let result = match result {
+            Some(r) => Rational::from(r),
+            None => {
+                prop_assert!(exact >= max - 1, "result should be representable");
+                return Ok(());
+            }
+        };
+
+        match rounding {
+            Rounding::Down => prop_assert!(result <= exact),
+            Rounding::Up => prop_assert!(result >= exact),
+            Rounding::Nearest => {}
+        }
+
+        // values above 2^-60 keep at least 62 bits of precision
+        if exact >= Rational::from((1, 1u128 << 60)) {
+            let error = (result - exact.clone()).abs() / exact;
+            prop_assert!(error <= Rational::from((1, 1u128 << 62)));
+        }
+        Ok(())
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(10_000))]
+        #[test]
+        fn arithmetic_is_rounded_correctly(a in ratio(), b in ratio(), rounding in rounding()) {
+            let (x, y) = (Rational::from(a), Rational::from(b));
+
+            assert_rounded(a.checked_add(&b, rounding), x.clone() + y.clone(), rounding)?;
+            assert_rounded(a.checked_mul(&b, rounding), x.clone() * y.clone(), rounding)?;
+            if !b.is_zero() {
+                assert_rounded(a.checked_div(&b, rounding), x.clone() / y.clone(), rounding)?;
+            }
+            if a >= b {
+                assert_rounded(a.checked_sub(&b, rounding), x - y, rounding)?;
+            } else {
+                prop_assert_eq!(a.checked_sub(&b, rounding), None);
+            }
+        }
+    }
+
+    fn reducible_ratio() -> impl Strategy<Value = Ratio> {
+        // common factor makes the ratios reducible
+        (1..u64::MAX as u128, 1..u64::MAX as u128, 1..u64::MAX as u128)
+            .prop_map(|(n, d, factor)| Ratio::new(n * factor, d * factor))
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(10_000))]
+        #[test]
+        fn reduce_preserves_value(x in reducible_ratio()) {
+            let reduced = x.reduce();
+            prop_assert_eq!(Rational::from(reduced), Rational::from(x));
+            prop_assert_eq!(Integer::from(reduced.n).gcd(&Integer::from(reduced.d)), 1);
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(10_000))]
+        #[test]
+        fn reduce_preserves_ordering(x in reducible_ratio(), y in reducible_ratio()) {
+            prop_assert_eq!(x.reduce().cmp(&y.reduce()), x.cmp(&y));
+            prop_assert_eq!(x.reduce() == y.reduce(), x.cmp(&y) == Ordering::Equal);
+        }
+    }
+}
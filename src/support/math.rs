@@ -0,0 +1,298 @@
+use crate::support::rational::Rounding;
+use crate::{ensure, MathError, MathError::Overflow};
+use core::convert::TryFrom;
+use primitive_types::{U256, U512};
+
+/// Calculate `a * b / denominator` with full precision of the intermediate product.
+/// `Nearest` rounds half up.
+pub fn mul_div(a: U256, b: U256, denominator: U256, rounding: Rounding) -> Result<U256, MathError> {
+    ensure!(!denominator.is_zero(), MathError::DivisionByZero);
+
+    let product = a.full_mul(b);
+    let denominator = U512::from(denominator);
+    let (quotient, remainder) = product.div_mod(denominator);
+
+    let round_up = match rounding {
+        Rounding::Down => false,
+        Rounding::Up => !remainder.is_zero(),
+        // Bare math is safe because `remainder < denominator`
+        Rounding::Nearest => remainder >= denominator - remainder,
+    };
+
+    let quotient = if round_up {
+        quotient.checked_add(U512::one()).ok_or(Overflow)?
+    } else {
+        quotient
+    };
+
+    U256::try_from(quotient).map_err(|_| Overflow)
+}
+
+/// Unsigned integer types supporting integer roots.
+pub trait IntegerRoot: Sized {
+    /// Square root of `self` rounded according to `rounding`.
+    fn sqrt(self, rounding: Rounding) -> Self;
+
+    /// `n`-th root of `self` rounded according to `rounding`.
+    ///
+    /// Returns `None` if `n` is zero.
+    fn nth_root(self, n: u32, rounding: Rounding) -> Option<Self>;
+}
+
+impl IntegerRoot for U256 {
+    fn sqrt(self, rounding: Rounding) -> Self {
+        root(self, 2, rounding)
+    }
+
+    fn nth_root(self, n: u32, rounding: Rounding) -> Option<Self> {
+        if n == 0 {
+            return None;
+        }
+        Some(root(self, n, rounding))
+    }
+}
+
+impl IntegerRoot for u128 {
+    fn sqrt(self, rounding: Rounding) -> Self {
+        // Root of u128 is at most 2^64, so it always fits into u128
+        root(U256::from(self), 2, rounding).low_u128()
+    }
+
+    fn nth_root(self, n: u32, rounding: Rounding) -> Option<Self> {
+        U256::from(self)
+            .nth_root(n, rounding)
+            .and_then(|r| u128::try_from(r).ok())
+    }
+}
+
+/// Square root of `x` rounded according to `rounding`.
+pub fn sqrt<T: IntegerRoot>(x: T, rounding: Rounding) -> T {
+    x.sqrt(rounding)
+}
+
+/// `n`-th root of `x` rounded according to `rounding`.
+///
+/// Returns `None` if `n` is zero.
+pub fn nth_root<T: IntegerRoot>(x: T, n: u32, rounding: Rounding) -> Option<T> {
+    x.nth_root(n, rounding)
+}
+
+/// `n`-th root of `x` for `n > 0`.
+fn root(x: U256, n: u32, rounding: Rounding) -> U256 {
+    let r = floor_root(x, n);
+
+    if r.is_zero() {
+        return r;
+    }
+
+    let round_up = match rounding {
+        Rounding::Down => false,
+        // Bare math is safe because r^n <= x
+        Rounding::Up => r.pow(U256::from(n)) != x,
+        Rounding::Nearest => is_at_least_half_power(x, r, n),
+    };
+
+    if round_up {
+        // Bare math is safe because the root is at most 2^128
+        r + U256::one()
+    } else {
+        r
+    }
+}
+
+/// Largest integer `r` such that `r^n <= x`, calculated by Newton's method.
+fn floor_root(x: U256, n: u32) -> U256 {
+    if x.is_zero() || n == 1 {
+        return x;
+    }
+
+    let bits = x.bits() as u32;
+    // x < 2^bits <= 2^n, so the root is less than 2
+    if n >= bits {
+        return U256::one();
+    }
+
+    // Initial guess 2^ceil(bits / n) is always greater than or equal to the root,
+    // so the iterations are decreasing until the root is reached
+    let mut r = U256::one() << ((bits + n - 1) / n);
+    let n_hp = U256::from(n);
+    let n_minus_one = U256::from(n - 1);
+
+    loop {
+        // If r^(n - 1) overflows, it is greater than x
+        let quotient = r.checked_pow(n_minus_one).map(|p| x / p).unwrap_or_else(U256::zero);
+        // r * (n - 1) does not overflow because r <= 2^ceil(256 / n)
+        let next = (r * n_minus_one + quotient) / n_hp;
+        if next >= r {
+            return r;
+        }
+        r = next;
+    }
+}
+
+/// Whether `x >= (r + 1/2)^n`, i.e. `x * 2^n >= (2r + 1)^n`, for `r >= 1`.
+///
+/// Both sides may not fit into `U512`, so `floor((2r + 1)^i / 2^i)` is calculated iteratively
+/// keeping the remainder `(2r + 1)^i mod 2^i` exactly.
+/// As `(2r + 1)^n` is odd, `(r + 1/2)^n` is never an integer and `x >= (r + 1/2)^n` iff `x > floor((r + 1/2)^n)`.
+fn is_at_least_half_power(x: U256, r: U256, n: u32) -> bool {
+    let base = (U512::from(r) << 1) + U512::one();
+    let x = U512::from(x);
+
+    let mut quotient = U512::one();
+    let mut remainder = U512::zero();
+
+    for i in 0..n {
+        // Remainder is less than 2^i and base is at most 2^(256 / n + 1),
+        // and the loop ends before i reaches 440, so none of these overflow
+        let t = base * remainder;
+        let carry = t >> i;
+        let low = t - (carry << i);
+
+        let v = base * quotient + carry;
+        quotient = v >> 1;
+        remainder = ((v & U512::one()) << i) + low;
+
+        if quotient > x {
+            return false;
+        }
+    }
+
+    x > quotient
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use rug::ops::Pow;
+    use rug::Integer;
+
+    fn to_integer(x: U256) -> Integer {
+        Integer::from_str_radix(&x.to_string(), 10).unwrap()
+    }
+
+    /// Exact root of `x` rounded according to `rounding`.
+    fn expected_root(x: &Integer, n: u32, rounding: Rounding) -> Integer {
+        let r = x.clone().root(n);
+        match rounding {
+            Rounding::Down => r,
+            Rounding::Up if r.clone().pow(n) == *x => r,
+            Rounding::Up => r + 1,
+            Rounding::Nearest => {
+                let doubled = Integer::from(2) * r.clone() + 1;
+                if (x.clone() << n) >= doubled.pow(n) {
+                    r + 1
+                } else {
+                    r
+                }
+            }
+        }
+    }
+
+    const ROUNDINGS: [Rounding; 3] = [Rounding::Down, Rounding::Up, Rounding::Nearest];
+
+    #[test]
+    fn mul_div_should_work() {
+        let (two, three, seven) = (U256::from(2), U256::from(3), U256::from(7));
+        assert_eq!(mul_div(two, three, seven, Rounding::Down), Ok(U256::zero()));
+        assert_eq!(mul_div(two, three, seven, Rounding::Up), Ok(U256::one()));
+        assert_eq!(mul_div(two, three, seven, Rounding::Nearest), Ok(U256::one()));
+        assert_eq!(mul_div(U256::one(), three, two, Rounding::Nearest), Ok(two));
+        assert_eq!(mul_div(U256::one(), two, seven, Rounding::Nearest), Ok(U256::zero()));
+        assert_eq!(mul_div(U256::MAX, U256::MAX, U256::MAX, Rounding::Up), Ok(U256::MAX));
+        assert_eq!(mul_div(U256::MAX, two, U256::one(), Rounding::Down), Err(Overflow));
+        assert_eq!(
+            mul_div(U256::MAX, U256::MAX, U256::MAX - 1, Rounding::Down),
+            Err(Overflow)
+        );
+        assert_eq!(
+            mul_div(two, three, U256::zero(), Rounding::Down),
+            Err(MathError::DivisionByZero)
+        );
+    }
+
+    #[test]
+    fn sqrt_should_work() {
+        assert_eq!(sqrt(0u128, Rounding::Up), 0);
+        assert_eq!(sqrt(1u128, Rounding::Nearest), 1);
+        assert_eq!(sqrt(15u128, Rounding::Down), 3);
+        assert_eq!(sqrt(15u128, Rounding::Up), 4);
+        assert_eq!(sqrt(15u128, Rounding::Nearest), 4);
+        assert_eq!(sqrt(12u128, Rounding::Nearest), 3);
+        assert_eq!(sqrt(13u128, Rounding::Nearest), 4);
+        assert_eq!(sqrt(16u128, Rounding::Up), 4);
+        assert_eq!(sqrt(u128::MAX, Rounding::Down), u64::MAX as u128);
+        assert_eq!(sqrt(u128::MAX, Rounding::Up), 1 << 64);
+        assert_eq!(sqrt(U256::MAX, Rounding::Down), U256::from(u128::MAX));
+        assert_eq!(sqrt(U256::MAX, Rounding::Up), U256::one() << 128);
+        assert_eq!(sqrt(U256::MAX, Rounding::Nearest), U256::one() << 128);
+    }
+
+    #[test]
+    fn nth_root_should_work() {
+        assert_eq!(nth_root(27u128, 0, Rounding::Down), None);
+        assert_eq!(nth_root(27u128, 1, Rounding::Down), Some(27));
+        assert_eq!(nth_root(27u128, 3, Rounding::Down), Some(3));
+        assert_eq!(nth_root(28u128, 3, Rounding::Up), Some(4));
+        assert_eq!(nth_root(u128::MAX, 128, Rounding::Down), Some(1));
+        assert_eq!(nth_root(u128::MAX, 128, Rounding::Up), Some(2));
+        assert_eq!(nth_root(u128::MAX, 200, Rounding::Nearest), Some(1));
+        assert_eq!(nth_root(U256::MAX, 256, Rounding::Nearest), Some(U256::from(2)));
+        assert_eq!(nth_root(U256::MAX, 437, Rounding::Nearest), Some(U256::from(2)));
+        assert_eq!(nth_root(U256::MAX, 438, Rounding::Nearest), Some(U256::one()));
+        assert_eq!(nth_root(U256::MAX, u32::MAX, Rounding::Nearest), Some(U256::one()));
+    }
+
+    #[test]
+    fn roots_of_small_numbers_should_be_exact() {
+        for x in 0..10_000u128 {
+            for n in 1..=8 {
+                for rounding in ROUNDINGS {
+                    let expected = expected_root(&Integer::from(x), n, rounding);
+                    assert_eq!(
+                        Integer::from(nth_root(x, n, rounding).unwrap()),
+                        expected,
+                        "root {} of {} rounded {:?}",
+                        n,
+                        x,
+                        rounding
+                    );
+                }
+            }
+        }
+    }
+
+    fn u256() -> impl Strategy<Value = U256> {
+        (any::<[u64; 4]>(), 0..=256u32).prop_map(|(limbs, bits)| {
+            let x = U256(limbs);
+            if bits == 256 {
+                x
+            } else {
+                x & ((U256::one() << bits) - 1)
+            }
+        })
+    }
+
+    fn rounding() -> impl Strategy<Value = Rounding> {
+        prop_oneof![Just(Rounding::Down), Just(Rounding::Up), Just(Rounding::Nearest)]
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(10_000))]
+        #[test]
+        fn roots_of_large_numbers_should_be_exact(x in u256(), n in 1..600u32, rounding in rounding()) {
+            let result = nth_root(x, n, rounding).unwrap();
+            prop_assert_eq!(to_integer(result), expected_root(&to_integer(x), n, rounding));
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(10_000))]
+        #[test]
+        fn square_roots_of_large_numbers_should_be_exact(x in u256(), y in any::<u128>(), rounding in rounding()) {
+            prop_assert_eq!(to_integer(sqrt(x, rounding)), expected_root(&to_integer(x), 2, rounding));
+            prop_assert_eq!(Integer::from(sqrt(y, rounding)), expected_root(&Integer::from(y), 2, rounding));
+        }
+    }
+}
@@ -1,3 +1,4 @@
+pub mod math;
 pub mod rational;
 pub mod traits;
 pub(crate) mod u128;
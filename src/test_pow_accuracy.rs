@@ -1,5 +1,8 @@
-use crate::transcendental::pow;
+use crate::transcendental::{exp_with_error, pow, pow_with_error, ErrorTarget};
 use crate::types::FixedBalance;
+use proptest::prelude::*;
+use rug::ops::Pow;
+use rug::{Float, Integer, Rational};
 use std::str::FromStr;
 
 fn ensure_accuracy(result: FixedBalance, expected: FixedBalance, tolerance: FixedBalance) -> bool {
@@ -5920,3 +5923,78 @@ fn pow_should_be_accurate() {
     let expected = S::from_str("331955811395453459.196536555252340306067698698247918467286706").unwrap();
     assert!(ensure_accuracy(result, expected, tolerance));
 }
+
+const PRECISION: u32 = 256;
+
+/// Convert a fixed point number to an arbitrary precision float without loss of precision.
+fn to_float(x: FixedBalance) -> Float {
+    let r = Rational::from((Integer::from(x.to_bits()), Integer::from(1) << FixedBalance::FRAC_NBITS));
+    Float::with_val(PRECISION, r)
+}
+
+fn fixed_in(min: f64, max: f64) -> impl Strategy<Value = FixedBalance> {
+    (FixedBalance::from_num(min).to_bits()..=FixedBalance::from_num(max).to_bits()).prop_map(FixedBalance::from_bits)
+}
+
+/// Base of the power when selling to a LBP pool with the max in ratio of 1/3.
+fn sell_base() -> impl Strategy<Value = FixedBalance> {
+    fixed_in(0.75, 1.0)
+}
+
+/// Base of the power when buying from a LBP pool with the max out ratio of 1/3.
+fn buy_base() -> impl Strategy<Value = FixedBalance> {
+    fixed_in(1.0, 1.5)
+}
+
+/// Ratio of LBP weights between 1% and 99%.
+fn weight_ratio() -> impl Strategy<Value = FixedBalance> {
+    fixed_in(0.01, 99.0)
+}
+
+fn target() -> FixedBalance {
+    FixedBalance::from_num(0.00000001)
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(1_000))]
+    #[test]
+    fn pow_with_error_is_within_bound_for_sell(base in sell_base(), exponent in weight_ratio()) {
+        let (result, error) = pow_with_error::<FixedBalance, FixedBalance>(base, exponent, ErrorTarget::Absolute(target())).unwrap();
+
+        let expected = to_float(base).pow(to_float(exponent));
+        let diff = (to_float(result) - expected).abs();
+
+        prop_assert!(error <= target());
+        prop_assert!(diff <= to_float(error), "result {} error {} diff {}", result, error, diff);
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(1_000))]
+    #[test]
+    fn pow_with_error_is_within_bound_for_buy(base in buy_base(), exponent in weight_ratio()) {
+        let (result, error) = pow_with_error::<FixedBalance, FixedBalance>(base, exponent, ErrorTarget::Relative(target())).unwrap();
+
+        let expected = to_float(base).pow(to_float(exponent));
+        let diff = (to_float(result) - expected.clone()).abs();
+
+        prop_assert!(to_float(error) <= expected * to_float(target()));
+        prop_assert!(diff <= to_float(error), "result {} error {} diff {}", result, error, diff);
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(1_000))]
+    #[test]
+    fn exp_with_error_is_within_bound(x in fixed_in(0.0, 40.0), neg in any::<bool>()) {
+        // e^x is at least one, so relative error is meaningful, while e^(-x) may be below precision
+        let target = if neg { ErrorTarget::Absolute(target()) } else { ErrorTarget::Relative(target()) };
+        let (result, error) = exp_with_error::<FixedBalance, FixedBalance>(x, neg, target).unwrap();
+
+        let x_float = if neg { -to_float(x) } else { to_float(x) };
+        let expected = x_float.exp();
+        let diff = (to_float(result) - expected).abs();
+
+        prop_assert!(diff <= to_float(error), "result {} error {} diff {}", result, error, diff);
+    }
+}
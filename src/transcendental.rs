@@ -16,15 +16,16 @@
 
 #![allow(clippy::result_unit_err)]
 
-use core::convert::From;
+use core::convert::{From, TryFrom};
 use core::ops::{AddAssign, BitOrAssign, ShlAssign, Shr, ShrAssign};
-use fixed::traits::{FixedUnsigned, ToFixed};
+use fixed::traits::{Fixed, FixedSigned, FixedUnsigned, ToFixed};
+use fixed::types::U64F64;
 use num_traits::{One, SaturatingMul, Zero};
 
 /// right-shift with rounding
 fn rs<T>(operand: T) -> T
 where
-    T: FixedUnsigned + One,
+    T: Fixed + One,
 {
     let lsb = T::one() >> T::FRAC_NBITS;
     (operand >> 1_u32) + (operand & lsb)
@@ -33,8 +34,8 @@ where
 /// base 2 logarithm assuming self >=1
 fn log2_inner<S, D>(operand: S) -> D
 where
-    S: FixedUnsigned + PartialOrd<D> + One,
-    D: FixedUnsigned + One,
+    S: Fixed + PartialOrd<D> + One,
+    D: Fixed + One,
     D::Bits: Copy + ToFixed + AddAssign + BitOrAssign + ShlAssign,
 {
     let two = D::from_num(2);
@@ -113,8 +114,50 @@ where
         return Ok(D::from(e));
     }
 
-    let operand = D::from(operand);
-    let mut result = operand + D::one();
+    let mut result = exp_inner(D::from(operand))?;
+
+    if neg {
+        result = D::one().checked_div(result).ok_or(())?;
+    }
+
+    Ok(result)
+}
+
+/// Remaining weight `2^(-elapsed / half_life)` after `elapsed` time given the `half_life`.
+///
+/// The whole half-lives are applied exactly by shifting, so only the remainder needs to be
+/// approximated via `exp`. Results below the precision of `D` are truncated to zero, as is
+/// everything with a `half_life` of zero.
+pub fn half_life_decay<D>(elapsed: u128, half_life: u128) -> Result<D, ()>
+where
+    D: FixedUnsigned + One + Zero,
+{
+    if half_life.is_zero() {
+        return Ok(D::zero());
+    }
+    let whole = elapsed / half_life;
+    let remainder = elapsed % half_life;
+    let whole = match u32::try_from(whole) {
+        Ok(whole) if whole < D::FRAC_NBITS => whole,
+        _ => return Ok(D::zero()),
+    };
+    // `2^(-r / h) = e^(-ln(2) * r / h)` with `r / h < 1`
+    let remainder = U64F64::checked_from_num(remainder).ok_or(())?;
+    let half_life = U64F64::checked_from_num(half_life).ok_or(())?;
+    let exponent = remainder
+        .checked_div(half_life)
+        .and_then(|ratio| ratio.checked_mul(U64F64::from_num(fixed::consts::LN_2)))
+        .ok_or(())?;
+    let partial = exp::<U64F64, U64F64>(exponent, true)?;
+    Ok(D::checked_from_num(partial.min(U64F64::one())).ok_or(())? >> whole)
+}
+
+/// exponential function e^(operand) assuming operand >= 0, calculated by taylor series
+fn exp_inner<D>(operand: D) -> Result<D, ()>
+where
+    D: Fixed + One,
+{
+    let mut result = operand.checked_add(D::one()).ok_or(())?;
     let mut term = operand;
 
     let max_iter = D::FRAC_NBITS.checked_mul(3).ok_or(())?;
@@ -125,10 +168,6 @@ where
         acc.checked_add(term).ok_or(())
     })?;
 
-    if neg {
-        result = D::one().checked_div(result).ok_or(())?;
-    }
-
     Ok(result)
 }
 
@@ -160,6 +199,108 @@ where
     Ok(result)
 }
 
+/// base 2 logarithm for signed fixed point numbers
+///
+/// Result is negative when operand is < 1.
+pub fn log2_signed<D>(operand: D) -> Result<D, ()>
+where
+    D: FixedSigned + One,
+    D::Bits: Copy + ToFixed + AddAssign + BitOrAssign + ShlAssign,
+{
+    if operand <= D::from_num(0) {
+        return Err(());
+    }
+
+    if operand < D::one() {
+        // operand = x * 2^-shift with 1 <= x < 2, so log2(operand) = log2(x) - shift
+        // Bare math is safe because operand < 1 has at least `INT_NBITS` leading zeros
+        let shift = operand.leading_zeros() + 1 - D::INT_NBITS;
+        let shift_d = D::checked_from_num(shift).ok_or(())?;
+        return log2_inner::<D, D>(operand << shift).checked_sub(shift_d).ok_or(());
+    }
+    Ok(log2_inner::<D, D>(operand))
+}
+
+/// natural logarithm for signed fixed point numbers
+///
+/// Result is negative when operand is < 1.
+pub fn ln_signed<D>(operand: D) -> Result<D, ()>
+where
+    D: FixedSigned + One,
+    D::Bits: Copy + ToFixed + AddAssign + BitOrAssign + ShlAssign,
+{
+    let log2_e = D::from_num(fixed::consts::LOG2_E);
+    log2_signed(operand)?.checked_div(log2_e).ok_or(())
+}
+
+/// exponential function e^(operand) for signed fixed point numbers
+pub fn exp_signed<D>(operand: D) -> Result<D, ()>
+where
+    D: FixedSigned + One + Zero,
+{
+    if operand.is_zero() {
+        return Ok(D::one());
+    }
+
+    if operand.is_negative() {
+        let neg_operand = match operand.checked_neg() {
+            Some(neg_operand) => neg_operand,
+            // e^x = (e^(x/2))^2 for x = D::MIN
+            None => {
+                let half = exp_signed(operand / D::from_num(2))?;
+                return Ok(half.saturating_mul(half));
+            }
+        };
+        return match exp_inner(neg_operand) {
+            Ok(result) => D::one().checked_div(result).ok_or(()),
+            Err(()) => exp_neg_reduced(neg_operand),
+        };
+    }
+    exp_inner(operand)
+}
+
+/// e^-x for x > 0 where e^x exceeds the range of `D`.
+///
+/// Uses e^-x = e^-r * 2^-k with x = k * ln(2) + r, so the result underflows to zero once it is
+/// below the precision of `D`.
+fn exp_neg_reduced<D>(operand: D) -> Result<D, ()>
+where
+    D: FixedSigned + One + Zero,
+{
+    let ln_2 = D::checked_from_num(fixed::consts::LN_2).ok_or(())?;
+    let k = operand.checked_div(ln_2).ok_or(())?.int();
+    let shift = match k.checked_to_num::<u32>() {
+        Some(shift) if shift < D::FRAC_NBITS => shift,
+        _ => return Ok(D::zero()),
+    };
+    let r = operand.checked_sub(ln_2.checked_mul(k).ok_or(())?).ok_or(())?;
+    let result = D::one().checked_div(exp_inner(r)?).ok_or(())?;
+    Ok(result >> shift)
+}
+
+/// power function with arbitrary signed fixed point number exponent
+///
+/// Operand must not be negative. Negative exponent results in the reciprocal of the power.
+pub fn pow_signed<D>(operand: D, exponent: D) -> Result<D, ()>
+where
+    D: FixedSigned + One + Zero,
+    D::Bits: Copy + ToFixed + AddAssign + BitOrAssign + ShlAssign,
+{
+    if operand.is_negative() {
+        return Err(());
+    } else if operand.is_zero() {
+        // zero to a negative power is undefined
+        return if exponent.is_negative() { Err(()) } else { Ok(D::zero()) };
+    } else if exponent.is_zero() {
+        return Ok(D::one());
+    } else if exponent == D::one() {
+        return Ok(operand);
+    }
+
+    let r = ln_signed(operand)?.checked_mul(exponent).ok_or(())?;
+    exp_signed(r)
+}
+
 /// power with integer exponent
 pub fn powi<S, D>(operand: S, exponent: u32) -> Result<D, ()>
 where
@@ -279,13 +420,225 @@ where
     None // if we do not have convergence, we do not risk returning an inaccurate value
 }
 
+/// Target error of the error-bounded functions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorTarget<D> {
+    /// Maximal absolute difference between the result and the exact value.
+    Absolute(D),
+    /// Maximal difference between the result and the exact value relative to the exact value.
+    Relative(D),
+}
+
+impl<D: Copy> ErrorTarget<D> {
+    fn value(&self) -> D {
+        match self {
+            ErrorTarget::Absolute(v) | ErrorTarget::Relative(v) => *v,
+        }
+    }
+}
+
+/// exponential function e^(operand) with guaranteed accuracy
+/// neg - bool indicates that operand is negative value.
+///
+/// Returns tuple(D,D) of the result and an upper bound of its absolute error.
+/// Fails if the target error can not be guaranteed in the precision of `D`.
+pub fn exp_with_error<S, D>(operand: S, neg: bool, target: ErrorTarget<D>) -> Result<(D, D), ()>
+where
+    S: FixedUnsigned,
+    D: FixedUnsigned + From<S> + One + Zero,
+{
+    if operand.is_zero() {
+        return Ok((D::one(), D::zero()));
+    }
+
+    let x = D::from(operand);
+    let (lo, hi) = exp_bounds(x, x, target.value() >> 2u32)?;
+
+    if neg {
+        let (lo, hi) = reciprocal_bounds(lo, hi)?;
+        return bounded_result(lo, hi, target);
+    }
+    bounded_result(lo, hi, target)
+}
+
+/// power function with arbitrary fixed point number exponent and guaranteed accuracy
+///
+/// Returns tuple(D,D) of the result and an upper bound of its absolute error.
+/// Fails if the target error can not be guaranteed in the precision of `D`.
+pub fn pow_with_error<S, D>(operand: S, exponent: S, target: ErrorTarget<D>) -> Result<(D, D), ()>
+where
+    S: FixedUnsigned + One + Zero,
+    D: FixedUnsigned + From<S> + One + Zero,
+{
+    if operand.is_zero() {
+        return Ok((D::zero(), D::zero()));
+    } else if exponent.is_zero() {
+        return Ok((D::one(), D::zero()));
+    } else if exponent == S::one() {
+        return Ok((D::from(operand), D::zero()));
+    }
+
+    let (x, y) = (D::from(operand), D::from(exponent));
+
+    // operand^exponent = 1 / (1 / operand)^exponent, so the logarithm is always non-negative
+    let neg = x < D::one();
+    let (u_lo, u_hi) = if neg { reciprocal_bounds(x, x)? } else { (x, x) };
+
+    let (ln_lo, ln_hi) = ln_bounds(u_lo, u_hi)?;
+    let (e_lo, e_hi) = (ln_lo.checked_mul(y).ok_or(())?, mul_up(ln_hi, y)?);
+
+    // Both e^x and its reciprocal are affected by the tail at most by the tail itself as e^x >= 1
+    let (lo, hi) = exp_bounds(e_lo, e_hi, target.value() >> 2u32)?;
+
+    if neg {
+        let (lo, hi) = reciprocal_bounds(lo, hi)?;
+        return bounded_result(lo, hi, target);
+    }
+    bounded_result(lo, hi, target)
+}
+
+/// smallest positive value of the fixed point type
+fn ulp<D: Fixed + One>() -> D {
+    D::one() >> D::FRAC_NBITS
+}
+
+/// multiplication rounded up (possibly by one unit more than necessary)
+fn mul_up<D: Fixed + One>(a: D, b: D) -> Result<D, ()> {
+    a.checked_mul(b).and_then(|v| v.checked_add(ulp())).ok_or(())
+}
+
+/// division rounded up (possibly by one unit more than necessary)
+fn div_up<D: Fixed + One>(a: D, b: D) -> Result<D, ()> {
+    a.checked_div(b).and_then(|v| v.checked_add(ulp())).ok_or(())
+}
+
+/// bounds of 1 / x given bounds of x > 0
+fn reciprocal_bounds<D: Fixed + One>(lo: D, hi: D) -> Result<(D, D), ()> {
+    Ok((D::one().checked_div(hi).ok_or(())?, div_up(D::one(), lo)?))
+}
+
+/// Result in the middle of the bounds together with its absolute error, if the error meets the target.
+fn bounded_result<D: Fixed + One>(lo: D, hi: D, target: ErrorTarget<D>) -> Result<(D, D), ()> {
+    let result = lo + ((hi - lo) >> 1u32);
+    let error = hi - result;
+
+    let max_error = match target {
+        ErrorTarget::Absolute(e) => e,
+        ErrorTarget::Relative(e) => lo.checked_mul(e).ok_or(())?,
+    };
+
+    if error > max_error {
+        return Err(());
+    }
+    Ok((result, error))
+}
+
+/// Lower bound of e^(x_lo) and upper bound of e^(x_hi) for 0 <= x_lo <= x_hi.
+///
+/// Terms of the taylor series are summed until the remaining terms are at most `tail_target`.
+fn exp_bounds<D>(x_lo: D, x_hi: D, tail_target: D) -> Result<(D, D), ()>
+where
+    D: Fixed + One,
+{
+    let two = D::from_num(2);
+    let (mut sum_lo, mut sum_hi) = (D::one(), D::one());
+    let (mut term_lo, mut term_hi) = (D::one(), D::one());
+
+    let max_iter = (D::INT_NBITS + D::FRAC_NBITS).checked_mul(4).ok_or(())?;
+    let double_x = x_hi.checked_mul(two).ok_or(())?;
+
+    for i in 1..max_iter {
+        let n = D::checked_from_num(i).ok_or(())?;
+        term_lo = term_lo.checked_mul(x_lo).and_then(|v| v.checked_div(n)).ok_or(())?;
+        term_hi = div_up(mul_up(term_hi, x_hi)?, n)?;
+
+        sum_lo = sum_lo.checked_add(term_lo).ok_or(())?;
+        sum_hi = sum_hi.checked_add(term_hi).ok_or(())?;
+
+        // Once i + 2 >= 2x, the remaining terms are bounded by a geometric series with ratio 1/2,
+        // so their sum is at most twice the next term.
+        if D::checked_from_num(i + 2).ok_or(())? >= double_x {
+            let next = D::checked_from_num(i + 1).ok_or(())?;
+            let tail = div_up(mul_up(term_hi, x_hi)?, next)?.checked_mul(two).ok_or(())?;
+            if tail <= tail_target {
+                return Ok((sum_lo, sum_hi.checked_add(tail).ok_or(())?));
+            }
+        }
+    }
+
+    Err(())
+}
+
+/// Lower bound of ln(u_lo) and upper bound of ln(u_hi) for 1 <= u_lo <= u_hi.
+///
+/// Operand is reduced to u = 2^k * m, with m in [1, 2), and ln(m) = 2 * atanh((m - 1) / (m + 1)).
+fn ln_bounds<D>(u_lo: D, u_hi: D) -> Result<(D, D), ()>
+where
+    D: Fixed + One + Zero,
+{
+    let one = D::one();
+    let two = D::from_num(2);
+
+    let mut k = 0u32;
+    let (mut m_lo, mut m_hi) = (u_lo, u_hi);
+    while m_lo >= two {
+        m_lo >>= 1u32;
+        m_hi = (m_hi >> 1u32).checked_add(ulp()).ok_or(())?;
+        k += 1;
+    }
+
+    // (m - 1) / (m + 1) is increasing in m
+    let z_lo = (m_lo - one).checked_div(m_lo.checked_add(one).ok_or(())?).ok_or(())?;
+    let z_hi = div_up(m_hi - one, m_hi.checked_add(one).ok_or(())?)?;
+    let z2_lo = z_lo.checked_mul(z_lo).ok_or(())?;
+    let z2_hi = mul_up(z_hi, z_hi)?;
+
+    // Tail estimate below requires z^2 <= 1/2
+    if z2_hi > one >> 1u32 {
+        return Err(());
+    }
+
+    let (mut power_lo, mut power_hi) = (z_lo, z_hi);
+    let (mut sum_lo, mut sum_hi) = (z_lo, z_hi);
+
+    let max_iter = (D::INT_NBITS + D::FRAC_NBITS).checked_mul(4).ok_or(())?;
+
+    for j in 1..max_iter {
+        power_lo = power_lo.checked_mul(z2_lo).ok_or(())?;
+        power_hi = mul_up(power_hi, z2_hi)?;
+
+        let d = D::checked_from_num(2 * j + 1).ok_or(())?;
+        sum_lo = sum_lo.checked_add(power_lo.checked_div(d).ok_or(())?).ok_or(())?;
+        sum_hi = sum_hi.checked_add(div_up(power_hi, d)?).ok_or(())?;
+
+        // Remaining terms are below the precision of `D`.
+        // Their sum is at most z^(2j+3) / (2j+3) / (1 - z^2), which is less than twice the next term.
+        if power_lo.is_zero() {
+            let next = D::checked_from_num(2 * j + 3).ok_or(())?;
+            let tail = div_up(mul_up(power_hi, z2_hi)?, next)?.checked_mul(two).ok_or(())?;
+            sum_hi = sum_hi.checked_add(tail).ok_or(())?;
+
+            let ln_2 = D::from_num(fixed::consts::LN_2);
+            let k = D::checked_from_num(k).ok_or(())?;
+            let ln_2_lo = ln_2.saturating_sub(ulp()).checked_mul(k).ok_or(())?;
+            let ln_2_hi = ln_2.checked_add(ulp()).and_then(|v| v.checked_mul(k)).ok_or(())?;
+
+            let lo = sum_lo.checked_mul(two).and_then(|v| v.checked_add(ln_2_lo)).ok_or(())?;
+            let hi = sum_hi.checked_mul(two).and_then(|v| v.checked_add(ln_2_hi)).ok_or(())?;
+            return Ok((lo, hi));
+        }
+    }
+
+    Err(())
+}
+
 #[cfg(test)]
 mod tests {
     use crate::fraction;
     use crate::types::{FixedBalance, Fraction};
     use core::str::FromStr;
     use fixed::traits::LossyInto;
-    use fixed::types::U64F64;
+    use fixed::types::{I64F64, U64F64};
 
     use super::*;
 
@@ -312,6 +665,29 @@ mod tests {
         );
     }
 
+    #[test]
+    fn half_life_decay_works() {
+        assert_eq!(half_life_decay::<U64F64>(0, 10), Ok(U64F64::one()));
+        assert_eq!(half_life_decay::<U64F64>(10, 10), Ok(U64F64::from_num(0.5)));
+        assert_eq!(half_life_decay::<U64F64>(30, 10), Ok(U64F64::from_num(0.125)));
+        assert_eq!(half_life_decay::<U64F64>(10, 0), Ok(U64F64::zero()));
+        assert_eq!(half_life_decay::<U64F64>(640, 10), Ok(U64F64::zero()));
+        assert_eq!(half_life_decay::<U64F64>(u128::MAX, 1), Ok(U64F64::zero()));
+        assert_eq!(half_life_decay::<Fraction>(630, 10), Ok(Fraction::from_num(1) >> 63));
+        assert_eq!(half_life_decay::<Fraction>(1_270, 10), Ok(Fraction::zero()));
+        assert_eq!(half_life_decay::<U64F64>(5, 1 << 100), Err(()));
+
+        // 2^(-1/2)
+        let decay = half_life_decay::<U64F64>(1, 2).unwrap();
+        let expected = U64F64::from_num(fixed::consts::FRAC_1_SQRT_2);
+        let diff = if decay > expected {
+            decay - expected
+        } else {
+            expected - decay
+        };
+        assert!(diff < U64F64::from_num(1e-16));
+    }
+
     #[test]
     fn log2_works() {
         type S = U64F64;
@@ -402,6 +778,172 @@ mod tests {
         assert_eq!(powi_near_one(S::from_num(9) / 10, 2), Some(S::from_num(81) / 100));
     }
 
+    #[test]
+    fn exp_signed_works() {
+        type D = I64F64;
+
+        let e = D::from_str("2.718281828459045235360287471352662497757").unwrap();
+
+        assert_eq!(exp_signed(D::from_num(0)), Ok(D::one()));
+        let expected: f64 = e.lossy_into();
+        let result: f64 = exp_signed(D::one()).unwrap().lossy_into();
+        assert_relative_eq!(result, expected, epsilon = 1.0e-15);
+        assert_eq!(
+            exp_signed(D::from_num(2)),
+            Ok(D::from_str("7.3890560989306502265").unwrap())
+        );
+        assert_eq!(
+            exp_signed(D::from_num(-2)),
+            Ok(D::from_str("0.13533528323661269186").unwrap())
+        );
+        // e^-43 * 2^64 = 3.9
+        assert_eq!(exp_signed(D::from_num(-43)).map(D::to_bits), Ok(3));
+        assert_eq!(exp_signed(D::from_num(-100)), Ok(D::from_num(0)));
+        assert_eq!(exp_signed(D::MIN), Ok(D::from_num(0)));
+    }
+
+    #[test]
+    fn log2_signed_works() {
+        type D = I64F64;
+
+        let one = D::one();
+        let two = D::from_num(2);
+        let four = D::from_num(4);
+
+        assert_eq!(log2_signed(D::from_num(0)), Err(()));
+        assert_eq!(log2_signed(-one), Err(()));
+
+        assert_eq!(log2_signed(one), Ok(D::from_num(0)));
+        assert_eq!(log2_signed(two), Ok(one));
+        assert_eq!(log2_signed(one / four), Ok(-two));
+        assert_eq!(log2_signed(D::from_num(0.5)), Ok(-one));
+        assert_eq!(log2_signed(D::DELTA), Ok(D::from_num(-64)));
+        assert_eq!(
+            log2_signed(D::DELTA * 3),
+            Ok(log2_inner::<D, D>(D::from_num(1.5)) - D::from_num(63))
+        );
+    }
+
+    #[test]
+    fn ln_signed_works() {
+        type D = I64F64;
+
+        let e = D::from_str("2.718281828459045235360287471352662497757").unwrap();
+
+        let result: f64 = ln_signed(e).unwrap().lossy_into();
+        assert_relative_eq!(result, 1.0, epsilon = 1.0e-15);
+
+        let result: f64 = ln_signed(D::one() / e).unwrap().lossy_into();
+        assert_relative_eq!(result, -1.0, epsilon = 1.0e-15);
+
+        assert_eq!(ln_signed(D::from_num(-1)), Err(()));
+
+        let result: f64 = ln_signed(D::DELTA).unwrap().lossy_into();
+        assert_relative_eq!(result, -64.0 * core::f64::consts::LN_2, epsilon = 1.0e-15);
+    }
+
+    #[test]
+    fn pow_signed_works() {
+        type D = I64F64;
+
+        let zero = D::from_num(0);
+        let one = D::one();
+        let two = D::from_num(2);
+        let three = D::from_num(3);
+        let four = D::from_num(4);
+
+        assert_eq!(pow_signed(two, zero), Ok(one));
+        assert_eq!(pow_signed(zero, two), Ok(zero));
+        assert_eq!(pow_signed(two, one), Ok(two));
+        assert_eq!(pow_signed(zero, -one), Err(()));
+        assert_eq!(pow_signed(-two, two), Err(()));
+
+        let result: f64 = pow_signed(two, three).unwrap().lossy_into();
+        assert_relative_eq!(result, 8.0, epsilon = 1.0e-6);
+
+        let result: f64 = pow_signed(two, -one).unwrap().lossy_into();
+        assert_relative_eq!(result, 0.5, epsilon = 1.0e-6);
+
+        let result: f64 = pow_signed(four, -one / two).unwrap().lossy_into();
+        assert_relative_eq!(result, 0.5, epsilon = 1.0e-6);
+
+        let result: f64 = pow_signed(one / four, -two).unwrap().lossy_into();
+        assert_relative_eq!(result, 16.0, epsilon = 1.0e-6);
+
+        let x = D::from_num(22.1234);
+        let y = D::from_num(2.1);
+        let result: f64 = (pow_signed(x, y).unwrap() * pow_signed(x, -y).unwrap()).lossy_into();
+        assert_relative_eq!(result, 1.0, epsilon = 1.0e-12);
+
+        // results below the precision underflow to zero
+        assert_eq!(pow_signed(one / two, D::from_num(100)), Ok(zero));
+        assert_eq!(pow_signed(two, D::from_num(-100)), Ok(zero));
+
+        // operands below 2^-63 do not have a representable reciprocal
+        let result: f64 = pow_signed(D::DELTA, one / two).unwrap().lossy_into();
+        assert_relative_eq!(result, 2.0_f64.powi(-32), max_relative = 1.0e-9);
+    }
+
+    fn distance(a: FixedBalance, b: FixedBalance) -> FixedBalance {
+        if a > b {
+            a - b
+        } else {
+            b - a
+        }
+    }
+
+    #[test]
+    fn pow_with_error_works() {
+        type S = FixedBalance;
+        type D = FixedBalance;
+
+        let zero = S::from_num(0);
+        let one = S::one();
+        let two = S::from_num(2);
+        let three = S::from_num(3);
+        let target = ErrorTarget::Absolute(D::from_num(0.000001));
+
+        assert_eq!(pow_with_error::<S, D>(two, zero, target), Ok((one, zero)));
+        assert_eq!(pow_with_error::<S, D>(zero, two, target), Ok((zero, zero)));
+        assert_eq!(pow_with_error::<S, D>(two, one, target), Ok((two, zero)));
+
+        let (result, error) = pow_with_error::<S, D>(two, three, target).unwrap();
+        assert!(error <= D::from_num(0.000001));
+        assert!(distance(result, D::from_num(8)) <= error);
+
+        let (result, error) = pow_with_error::<S, D>(one / 4, one / 2, target).unwrap();
+        assert!(distance(result, D::from_num(0.5)) <= error);
+
+        let (result, error) = pow_with_error::<S, D>(two, three, ErrorTarget::Relative(D::from_num(0.000001))).unwrap();
+        assert!(distance(result, D::from_num(8)) <= error);
+
+        // exact result can not be guaranteed
+        assert_eq!(pow_with_error::<S, D>(two, three, ErrorTarget::Absolute(zero)), Err(()));
+    }
+
+    #[test]
+    fn exp_with_error_works() {
+        type S = FixedBalance;
+        type D = FixedBalance;
+
+        let zero = S::from_num(0);
+        let one = S::one();
+        let target = ErrorTarget::Absolute(D::from_num(0.000001));
+        let e = D::from_str("2.718281828459045235360287471352662497757").unwrap();
+
+        assert_eq!(exp_with_error::<S, D>(zero, false, target), Ok((one, zero)));
+
+        let (result, error) = exp_with_error::<S, D>(one, false, target).unwrap();
+        assert!(error <= D::from_num(0.000001));
+        assert!(distance(result, e) <= error);
+
+        let (result, error) = exp_with_error::<S, D>(one, true, target).unwrap();
+        assert!(distance(result, one / e) <= error);
+
+        assert_eq!(exp_with_error::<S, D>(one, false, ErrorTarget::Absolute(zero)), Err(()));
+        assert_eq!(exp_with_error::<S, D>(S::from_num(100), false, target), Err(()));
+    }
+
     #[test]
     fn pow_works() {
         type S = FixedBalance;
@@ -0,0 +1,84 @@
+use crate::types::{Balance, LBPWeight, HYDRA_ONE};
+use crate::weighted_pool::*;
+use proptest::prelude::*;
+
+const ONE: Balance = HYDRA_ONE;
+
+fn asset_reserve() -> impl Strategy<Value = Balance> {
+    1000 * ONE..10_000_000 * ONE
+}
+
+fn trade_amount() -> impl Strategy<Value = Balance> {
+    ONE..100 * ONE
+}
+
+fn weight() -> impl Strategy<Value = LBPWeight> {
+    1_000_000..50_000_000u32
+}
+
+fn pool() -> impl Strategy<Value = (Vec<Balance>, Vec<LBPWeight>)> {
+    (3..=8usize).prop_flat_map(|n| {
+        (
+            prop::collection::vec(asset_reserve(), n),
+            prop::collection::vec(weight(), n),
+        )
+    })
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(1000))]
+    #[test]
+    fn all_asset_join_and_exit_does_not_leak_value(
+        (balances, _weights) in pool(),
+        shares in trade_amount(),
+        issuance in asset_reserve(),
+    ) {
+        let amounts_in = calculate_all_asset_join(&balances, shares, issuance).unwrap();
+
+        let new_balances: Vec<Balance> = balances.iter().zip(amounts_in.iter()).map(|(b, a)| b + a).collect();
+        let amounts_out = calculate_all_asset_exit(&new_balances, shares, issuance + shares).unwrap();
+
+        for (amount_in, amount_out) in amounts_in.iter().zip(amounts_out.iter()) {
+            prop_assert!(amount_out <= amount_in);
+        }
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(1000))]
+    #[test]
+    fn single_asset_join_and_exit_does_not_leak_value(
+        (balances, weights) in pool(),
+        idx in 0..3usize,
+        amount in trade_amount(),
+        issuance in asset_reserve(),
+    ) {
+        let shares = calculate_single_asset_join(&balances, &weights, idx, amount, issuance).unwrap();
+
+        let mut new_balances = balances.clone();
+        new_balances[idx] += amount;
+
+        let amount_out = calculate_single_asset_exit(&new_balances, &weights, idx, shares, issuance + shares).unwrap();
+
+        // allow for the precision of the fixed point arithmetic used by `pow`
+        let tolerance = amount / 1_000_000_000;
+        prop_assert!(amount_out <= amount + tolerance, "{} > {}", amount_out, amount);
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(1000))]
+    #[test]
+    fn single_asset_join_gives_no_more_than_proportional_join(
+        (balances, weights) in pool(),
+        idx in 0..3usize,
+        amount in trade_amount(),
+        issuance in asset_reserve(),
+    ) {
+        // Adding a single asset can never be worth more shares than its proportional part of the pool
+        let shares = calculate_single_asset_join(&balances, &weights, idx, amount, issuance).unwrap();
+
+        let proportional_shares = primitive_types::U256::from(issuance) * primitive_types::U256::from(amount) / primitive_types::U256::from(balances[idx]);
+        prop_assert!(primitive_types::U256::from(shares) <= proportional_shares);
+    }
+}
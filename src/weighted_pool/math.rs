@@ -0,0 +1,294 @@
+use crate::lbp::{convert_from_fixed, convert_to_fixed, round_up_fixed};
+use crate::types::{Balance, FixedBalance, LBPWeight};
+use crate::{
+    ensure, lbp, to_balance, to_balance_from_fixed, to_fixed_balance, to_u256, MathError,
+    MathError::{InsufficientOutReserve, InvalidAssetIndex, Overflow, ZeroReserve, ZeroWeight},
+};
+use core::convert::TryFrom;
+use num_traits::{One, Zero};
+use primitive_types::U256;
+use sp_std::prelude::*;
+
+/// Calculating spot price of asset out denominated in asset in for given amount.
+/// Formula : AMOUNT * OUT_RESERVE * IN_WEIGHT / (IN_RESERVE * OUT_WEIGHT)
+///
+/// - `balances` - reserves of all assets in the pool
+/// - `weights` - weights of all assets in the pool
+/// - `idx_in` - index of selling asset
+/// - `idx_out` - index of buying asset
+/// - `amount` - amount
+///
+/// Returns MathError in case of error
+pub fn calculate_spot_price(
+    balances: &[Balance],
+    weights: &[LBPWeight],
+    idx_in: usize,
+    idx_out: usize,
+    amount: Balance,
+) -> Result<Balance, MathError> {
+    ensure_valid_pool(balances, weights)?;
+    ensure!(idx_in < balances.len() && idx_out < balances.len(), InvalidAssetIndex);
+
+    lbp::calculate_spot_price(
+        balances[idx_in],
+        balances[idx_out],
+        weights[idx_in],
+        weights[idx_out],
+        amount,
+    )
+}
+
+/// Calculating amount to be received from the pool given the amount to be sent to the pool.
+/// Only the two traded assets are involved, so this delegates to `lbp::calculate_out_given_in`.
+/// Formula : OUT_RESERVE * (1 - (IN_RESERVE / (IN_RESERVE + AMOUNT)) ^ (IN_WEIGHT / OUT_WEIGHT))
+///
+/// - `balances` - reserves of all assets in the pool
+/// - `weights` - weights of all assets in the pool
+/// - `idx_in` - index of selling asset
+/// - `idx_out` - index of buying asset
+/// - `amount_in` - amount sent to the pool
+///
+/// Returns MathError in case of error
+pub fn calculate_out_given_in(
+    balances: &[Balance],
+    weights: &[LBPWeight],
+    idx_in: usize,
+    idx_out: usize,
+    amount_in: Balance,
+) -> Result<Balance, MathError> {
+    ensure_valid_pool(balances, weights)?;
+    ensure!(idx_in < balances.len() && idx_out < balances.len(), InvalidAssetIndex);
+    ensure!(idx_in != idx_out, InvalidAssetIndex);
+
+    lbp::calculate_out_given_in(
+        balances[idx_in],
+        balances[idx_out],
+        weights[idx_in],
+        weights[idx_out],
+        amount_in,
+    )
+}
+
+/// Calculating amount to be sent to the pool given the amount to be received from the pool.
+/// Only the two traded assets are involved, so this delegates to `lbp::calculate_in_given_out`.
+/// Formula : IN_RESERVE * ((OUT_RESERVE / (OUT_RESERVE - AMOUNT)) ^ (OUT_WEIGHT / IN_WEIGHT) - 1)
+///
+/// - `balances` - reserves of all assets in the pool
+/// - `weights` - weights of all assets in the pool
+/// - `idx_in` - index of selling asset
+/// - `idx_out` - index of buying asset
+/// - `amount_out` - amount received from the pool
+///
+/// Returns MathError in case of error
+pub fn calculate_in_given_out(
+    balances: &[Balance],
+    weights: &[LBPWeight],
+    idx_in: usize,
+    idx_out: usize,
+    amount_out: Balance,
+) -> Result<Balance, MathError> {
+    ensure_valid_pool(balances, weights)?;
+    ensure!(idx_in < balances.len() && idx_out < balances.len(), InvalidAssetIndex);
+    ensure!(idx_in != idx_out, InvalidAssetIndex);
+    ensure!(amount_out < balances[idx_out], InsufficientOutReserve);
+
+    if amount_out.is_zero() {
+        return Ok(Balance::zero());
+    }
+
+    lbp::calculate_in_given_out(
+        balances[idx_in],
+        balances[idx_out],
+        weights[idx_in],
+        weights[idx_out],
+        amount_out,
+    )
+}
+
+/// Calculating amounts of all assets to be sent to the pool to receive given amount of shares.
+/// Formula : AMOUNT_i = RESERVE_i * SHARES / SHARE_ISSUANCE
+///
+/// Amounts are rounded up.
+///
+/// - `balances` - reserves of all assets in the pool
+/// - `shares` - amount of shares to receive
+/// - `share_issuance` - total issuance of share asset
+///
+/// Returns MathError in case of error
+pub fn calculate_all_asset_join(
+    balances: &[Balance],
+    shares: Balance,
+    share_issuance: Balance,
+) -> Result<Vec<Balance>, MathError> {
+    ensure!(share_issuance != 0, ZeroReserve);
+
+    balances
+        .iter()
+        .map(|reserve| {
+            let (reserve_hp, shares_hp, issuance_hp) = to_u256!(*reserve, shares, share_issuance);
+            let amount = reserve_hp
+                .checked_mul(shares_hp)
+                .and_then(|v| v.checked_add(issuance_hp.checked_sub(U256::one())?))
+                .and_then(|v| v.checked_div(issuance_hp))
+                .ok_or(Overflow)?;
+            to_balance!(amount)
+        })
+        .collect()
+}
+
+/// Calculating amounts of all assets to be received from the pool for given amount of shares.
+/// Formula : AMOUNT_i = RESERVE_i * SHARES / SHARE_ISSUANCE
+///
+/// Amounts are rounded down.
+///
+/// - `balances` - reserves of all assets in the pool
+/// - `shares` - amount of shares to redeem
+/// - `share_issuance` - total issuance of share asset
+///
+/// Returns MathError in case of error
+pub fn calculate_all_asset_exit(
+    balances: &[Balance],
+    shares: Balance,
+    share_issuance: Balance,
+) -> Result<Vec<Balance>, MathError> {
+    ensure!(share_issuance != 0, ZeroReserve);
+    ensure!(shares <= share_issuance, InsufficientOutReserve);
+
+    balances
+        .iter()
+        .map(|reserve| {
+            let (reserve_hp, shares_hp, issuance_hp) = to_u256!(*reserve, shares, share_issuance);
+            let amount = reserve_hp
+                .checked_mul(shares_hp)
+                .and_then(|v| v.checked_div(issuance_hp))
+                .ok_or(Overflow)?;
+            to_balance!(amount)
+        })
+        .collect()
+}
+
+/// Calculating amount of shares to be received for adding liquidity of a single asset.
+/// Formula : SHARES = SHARE_ISSUANCE * ((1 + AMOUNT / RESERVE) ^ (WEIGHT / TOTAL_WEIGHT) - 1)
+///
+/// - `balances` - reserves of all assets in the pool
+/// - `weights` - weights of all assets in the pool
+/// - `idx_in` - index of added asset
+/// - `amount_in` - amount of asset added
+/// - `share_issuance` - total issuance of share asset
+///
+/// Returns MathError in case of error
+pub fn calculate_single_asset_join(
+    balances: &[Balance],
+    weights: &[LBPWeight],
+    idx_in: usize,
+    amount_in: Balance,
+    share_issuance: Balance,
+) -> Result<Balance, MathError> {
+    ensure_valid_pool(balances, weights)?;
+    ensure!(idx_in < balances.len(), InvalidAssetIndex);
+
+    if amount_in.is_zero() {
+        return Ok(Balance::zero());
+    }
+
+    let total_weight = calculate_total_weight(weights)?;
+
+    let (weight, total_weight, reserve, amount, issuance) = to_fixed_balance!(
+        weights[idx_in] as u128,
+        total_weight,
+        balances[idx_in],
+        amount_in,
+        share_issuance
+    );
+
+    // We are correctly rounding these down
+    let weight_ratio = weight.checked_div(total_weight).ok_or(Overflow)?;
+    let new_reserve = reserve.checked_add(amount).ok_or(Overflow)?;
+    let reserve_ratio = new_reserve.checked_div(reserve).ok_or(Overflow)?;
+
+    let r: FixedBalance = crate::transcendental::pow(reserve_ratio, weight_ratio).map_err(|_| Overflow)?;
+
+    // We round this down to prevent giving out too many shares
+    let growth = round_down_fixed(r.checked_sub(FixedBalance::one()).ok_or(Overflow)?);
+
+    let shares = issuance.checked_mul(growth).ok_or(Overflow)?;
+
+    to_balance_from_fixed!(shares)
+}
+
+/// Calculating amount of single asset to be received for removing given amount of shares.
+/// Formula : AMOUNT = RESERVE * (1 - (1 - SHARES / SHARE_ISSUANCE) ^ (TOTAL_WEIGHT / WEIGHT))
+///
+/// - `balances` - reserves of all assets in the pool
+/// - `weights` - weights of all assets in the pool
+/// - `idx_out` - index of removed asset
+/// - `shares` - amount of shares to redeem
+/// - `share_issuance` - total issuance of share asset
+///
+/// Returns MathError in case of error
+pub fn calculate_single_asset_exit(
+    balances: &[Balance],
+    weights: &[LBPWeight],
+    idx_out: usize,
+    shares: Balance,
+    share_issuance: Balance,
+) -> Result<Balance, MathError> {
+    ensure_valid_pool(balances, weights)?;
+    ensure!(idx_out < balances.len(), InvalidAssetIndex);
+    ensure!(share_issuance != 0, ZeroReserve);
+    // Removing all shares would require removing the whole reserve of every asset
+    ensure!(shares < share_issuance, InsufficientOutReserve);
+
+    if shares.is_zero() {
+        return Ok(Balance::zero());
+    }
+
+    let total_weight = calculate_total_weight(weights)?;
+
+    let (weight, total_weight, reserve, shares, issuance) = to_fixed_balance!(
+        weights[idx_out] as u128,
+        total_weight,
+        balances[idx_out],
+        shares,
+        share_issuance
+    );
+
+    // We round this up
+    // The bigger the remaining share ratio and exponent, the smaller the amount out
+    let weight_ratio = round_up_fixed(total_weight.checked_div(weight).ok_or(Overflow)?)?;
+    let remaining_issuance = issuance.checked_sub(shares).ok_or(Overflow)?;
+    let issuance_ratio = round_up_fixed(remaining_issuance.checked_div(issuance).ok_or(Overflow)?)?;
+
+    let r: FixedBalance = crate::transcendental::pow(issuance_ratio, weight_ratio).map_err(|_| Overflow)?;
+
+    // We round this up to prevent giving out too much
+    let remaining_reserve_ratio = round_up_fixed(r)?;
+    let removed_ratio = FixedBalance::one().saturating_sub(remaining_reserve_ratio);
+
+    let amount_out = reserve.checked_mul(removed_ratio).ok_or(Overflow)?;
+
+    to_balance_from_fixed!(amount_out)
+}
+
+/// Every asset needs a non-zero reserve and weight, so a missing weight is reported as `ZeroWeight`
+/// and a missing reserve as `ZeroReserve`.
+fn ensure_valid_pool(balances: &[Balance], weights: &[LBPWeight]) -> Result<(), MathError> {
+    ensure!(weights.len() >= balances.len(), ZeroWeight);
+    ensure!(balances.len() >= weights.len(), ZeroReserve);
+    ensure!(balances.len() >= 2, ZeroReserve);
+    ensure!(weights.iter().all(|w| *w != 0), ZeroWeight);
+    ensure!(balances.iter().all(|b| *b != 0), ZeroReserve);
+    Ok(())
+}
+
+fn calculate_total_weight(weights: &[LBPWeight]) -> Result<Balance, MathError> {
+    weights
+        .iter()
+        .try_fold(Balance::zero(), |acc, w| acc.checked_add(*w as Balance))
+        .ok_or(Overflow)
+}
+
+fn round_down_fixed(value: FixedBalance) -> FixedBalance {
+    let prec = FixedBalance::from_num(0.00000000001);
+    value.saturating_sub(prec)
+}
@@ -0,0 +1,161 @@
+use crate::lbp;
+use crate::types::{Balance, LBPWeight, HYDRA_ONE};
+use crate::weighted_pool::*;
+use crate::MathError::{InsufficientOutReserve, InvalidAssetIndex, ZeroReserve, ZeroWeight};
+
+use super::super::test_utils::assert_approx_eq;
+use std::vec;
+
+const ONE: Balance = HYDRA_ONE;
+
+const BALANCES: [Balance; 3] = [100 * ONE, 200 * ONE, 300 * ONE];
+const WEIGHTS: [LBPWeight; 3] = [20_000_000, 30_000_000, 50_000_000];
+
+#[test]
+fn spot_price_should_work() {
+    assert_eq!(
+        calculate_spot_price(&BALANCES, &WEIGHTS, 0, 2, ONE),
+        lbp::calculate_spot_price(100 * ONE, 300 * ONE, 20_000_000, 50_000_000, ONE)
+    );
+    assert_eq!(
+        calculate_spot_price(&BALANCES, &WEIGHTS, 0, 2, ONE),
+        Ok(1_200_000_000_000)
+    );
+    assert_eq!(
+        calculate_spot_price(&BALANCES, &WEIGHTS, 2, 1, ONE),
+        Ok(1_111_111_111_111)
+    );
+    assert_eq!(
+        calculate_spot_price(&BALANCES, &WEIGHTS, 0, 3, ONE),
+        Err(InvalidAssetIndex)
+    );
+}
+
+#[test]
+fn out_given_in_should_work() {
+    assert_eq!(
+        calculate_out_given_in(&BALANCES, &WEIGHTS, 0, 2, 10 * ONE),
+        lbp::calculate_out_given_in(100 * ONE, 300 * ONE, 20_000_000, 50_000_000, 10 * ONE)
+    );
+    assert_eq!(
+        calculate_out_given_in(&BALANCES, &WEIGHTS, 1, 0, 10 * ONE),
+        lbp::calculate_out_given_in(200 * ONE, 100 * ONE, 30_000_000, 20_000_000, 10 * ONE)
+    );
+
+    let cases = vec![
+        (vec![100, 200], vec![1, 1, 1], 0, 1, Err(ZeroReserve), "Missing reserve"),
+        (vec![100], vec![1], 0, 0, Err(ZeroReserve), "Single asset"),
+        (vec![100, 200, 300], vec![1, 0, 1], 0, 1, Err(ZeroWeight), "Zero weight"),
+        (vec![100, 0, 300], vec![1, 1, 1], 0, 2, Err(ZeroReserve), "Zero reserve"),
+        (
+            vec![100, 200, 300],
+            vec![1, 1, 1],
+            0,
+            3,
+            Err(InvalidAssetIndex),
+            "Index out of bounds",
+        ),
+        (vec![100, 200, 300], vec![1, 1], 0, 1, Err(ZeroWeight), "Missing weight"),
+        (
+            vec![100, 200, 300],
+            vec![1, 1, 1],
+            1,
+            1,
+            Err(InvalidAssetIndex),
+            "Same asset",
+        ),
+    ];
+
+    for case in cases {
+        assert_eq!(
+            calculate_out_given_in(&case.0, &case.1, case.2, case.3, 10),
+            case.4,
+            "{}",
+            case.5
+        );
+    }
+}
+
+#[test]
+fn in_given_out_should_work() {
+    assert_eq!(
+        calculate_in_given_out(&BALANCES, &WEIGHTS, 0, 2, 10 * ONE),
+        lbp::calculate_in_given_out(100 * ONE, 300 * ONE, 20_000_000, 50_000_000, 10 * ONE)
+    );
+    assert_eq!(calculate_in_given_out(&BALANCES, &WEIGHTS, 0, 2, 0), Ok(0));
+    assert_eq!(
+        calculate_in_given_out(&BALANCES, &WEIGHTS, 0, 2, 300 * ONE),
+        Err(InsufficientOutReserve)
+    );
+}
+
+#[test]
+fn all_asset_join_and_exit_should_work() {
+    let issuance = 1000 * ONE;
+
+    assert_eq!(
+        calculate_all_asset_join(&BALANCES, 10 * ONE, issuance),
+        Ok(vec![ONE, 2 * ONE, 3 * ONE])
+    );
+    assert_eq!(
+        calculate_all_asset_exit(&BALANCES, 10 * ONE, issuance),
+        Ok(vec![ONE, 2 * ONE, 3 * ONE])
+    );
+
+    // join rounds up, exit rounds down
+    assert_eq!(
+        calculate_all_asset_join(&[1000, 2001, 3003], 7, 10),
+        Ok(vec![700, 1401, 2103])
+    );
+    assert_eq!(
+        calculate_all_asset_exit(&[1000, 2001, 3003], 7, 10),
+        Ok(vec![700, 1400, 2102])
+    );
+
+    assert_eq!(calculate_all_asset_join(&BALANCES, ONE, 0), Err(ZeroReserve));
+    assert_eq!(calculate_all_asset_exit(&BALANCES, ONE, 0), Err(ZeroReserve));
+    assert_eq!(
+        calculate_all_asset_exit(&BALANCES, issuance + 1, issuance),
+        Err(InsufficientOutReserve)
+    );
+}
+
+#[test]
+fn single_asset_join_should_work() {
+    let issuance = 1000 * ONE;
+    let tolerance = 10_000_000;
+
+    let shares = calculate_single_asset_join(&BALANCES, &WEIGHTS, 0, 10 * ONE, issuance).unwrap();
+    assert_approx_eq!(shares, 19_244_876_491_456, tolerance);
+
+    let shares = calculate_single_asset_join(&BALANCES, &WEIGHTS, 2, 10 * ONE, issuance).unwrap();
+    assert_approx_eq!(shares, 16_530_045_465_127, tolerance);
+
+    assert_eq!(calculate_single_asset_join(&BALANCES, &WEIGHTS, 2, 0, issuance), Ok(0));
+    assert_eq!(
+        calculate_single_asset_join(&BALANCES, &WEIGHTS, 3, ONE, issuance),
+        Err(InvalidAssetIndex)
+    );
+}
+
+#[test]
+fn single_asset_exit_should_work() {
+    let issuance = 1000 * ONE;
+    let tolerance = 10_000_000;
+
+    let amount = calculate_single_asset_exit(&BALANCES, &WEIGHTS, 0, 10 * ONE, issuance).unwrap();
+    assert_approx_eq!(amount, 4_900_995_010_000, tolerance);
+
+    let amount = calculate_single_asset_exit(&BALANCES, &WEIGHTS, 2, 10 * ONE, issuance).unwrap();
+    assert_approx_eq!(amount, 5_970_000_000_000, tolerance);
+
+    assert_eq!(calculate_single_asset_exit(&BALANCES, &WEIGHTS, 2, 0, issuance), Ok(0));
+    assert_eq!(
+        calculate_single_asset_exit(&BALANCES, &WEIGHTS, 2, issuance, issuance),
+        Err(InsufficientOutReserve)
+    );
+    assert_eq!(
+        calculate_single_asset_exit(&BALANCES, &WEIGHTS, 2, ONE, 0),
+        Err(ZeroReserve)
+    );
+}
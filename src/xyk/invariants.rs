@@ -16,6 +16,10 @@ fn trade_amount() -> impl Strategy<Value = Balance> {
     ONE..100 * ONE
 }
 
+fn fee() -> impl Strategy<Value = (u32, u32)> {
+    (0u32..1000u32, Just(1000u32))
+}
+
 fn assert_asset_invariant(
     old_state: (Balance, Balance),
     new_state: (Balance, Balance),
@@ -37,6 +41,13 @@ fn assert_asset_invariant(
     assert_approx_eq!(invariant, FixedU128::from(1u128), tolerance, desc);
 }
 
+fn assert_invariant_not_decreased(old_state: (Balance, Balance), new_state: (Balance, Balance), desc: &str) {
+    let new_s = U256::from(new_state.0) * U256::from(new_state.1);
+    let old_s = U256::from(old_state.0) * U256::from(old_state.1);
+
+    assert!(new_s >= old_s, "Invariant decreased for {desc}");
+}
+
 proptest! {
     #![proptest_config(ProptestConfig::with_cases(1000))]
     #[test]
@@ -71,6 +82,50 @@ proptest! {
     }
 }
 
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(1000))]
+    #[test]
+    fn sell_with_fee_invariants( asset_in_reserve in asset_reserve(),
+        asset_out_reserve in asset_reserve(),
+        amount in  trade_amount(),
+        fee in fee(),
+    ) {
+        let (amount_out, fee_amount) = crate::xyk::calculate_out_given_in_with_fee(asset_in_reserve, asset_out_reserve, amount, fee).unwrap();
+        let amount_out_no_fee = crate::xyk::calculate_out_given_in(asset_in_reserve, asset_out_reserve, amount).unwrap();
+
+        assert_eq!(amount_out + fee_amount, amount_out_no_fee);
+        assert!(U256::from(fee_amount) * U256::from(fee.1) >= U256::from(amount_out_no_fee) * U256::from(fee.0));
+
+        // Fee remains in the pool so the invariant can only grow
+        assert_invariant_not_decreased((asset_in_reserve, asset_out_reserve),
+            (asset_in_reserve + amount, asset_out_reserve - amount_out),
+            "out given in with fee"
+        );
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(1000))]
+    #[test]
+    fn buy_with_fee_invariants( asset_in_reserve in asset_reserve(),
+        asset_out_reserve in asset_reserve(),
+        amount in  trade_amount(),
+        fee in fee(),
+    ) {
+        let (amount_in, fee_amount) = crate::xyk::calculate_in_given_out_with_fee(asset_out_reserve, asset_in_reserve, amount, fee).unwrap();
+        let amount_in_no_fee = crate::xyk::calculate_in_given_out(asset_out_reserve, asset_in_reserve, amount).unwrap();
+
+        assert_eq!(amount_in, amount_in_no_fee + fee_amount);
+        assert!(U256::from(fee_amount) * U256::from(fee.1) >= U256::from(amount_in_no_fee) * U256::from(fee.0));
+
+        // Fee remains in the pool so the invariant can only grow
+        assert_invariant_not_decreased((asset_in_reserve, asset_out_reserve),
+            (asset_in_reserve + amount_in, asset_out_reserve - amount),
+            "in given out with fee"
+        );
+    }
+}
+
 proptest! {
     #![proptest_config(ProptestConfig::with_cases(1000))]
     #[test]
@@ -156,3 +211,66 @@ proptest! {
         assert!(l >= r);
     }
 }
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(1000))]
+    #[test]
+    fn zap_in_and_out( asset_a_reserve in asset_reserve(),
+        asset_b_reserve in asset_reserve(),
+        amount in  trade_amount(),
+        fee in fee(),
+        issuance in asset_reserve(),
+    ) {
+        let (swap_amount, amount_a, amount_b, shares) = crate::xyk::calculate_zap_in(asset_a_reserve, asset_b_reserve, amount, fee, issuance).unwrap();
+
+        assert_eq!(swap_amount + amount_a, amount);
+
+        // Remaining amounts should match the pool ratio after the swap up to a few units of rounding
+        let (swapped_b, _) = crate::xyk::calculate_out_given_in_with_fee(asset_a_reserve, asset_b_reserve, swap_amount, fee).unwrap();
+        assert_eq!(swapped_b, amount_b);
+
+        let x = U256::from(asset_a_reserve + swap_amount);
+        let y = U256::from(asset_b_reserve - amount_b);
+        let l = U256::from(amount_a) * y;
+        let r = U256::from(amount_b) * x;
+        let diff = if l >= r { l - r } else { r - l };
+        assert!(diff <= U256::from(4) * (x + y));
+
+        // Zapping out the received shares must not return more than was provided
+        let (amount_back, _, _) = crate::xyk::calculate_zap_out(
+            asset_a_reserve + amount,
+            asset_b_reserve,
+            shares,
+            fee,
+            issuance + shares,
+        ).unwrap();
+
+        assert!(amount_back <= amount);
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(1000))]
+    #[test]
+    fn twap_of_constant_reserves_equals_spot_price( asset_a_reserve in asset_reserve(),
+        asset_b_reserve in asset_reserve(),
+        start in any::<u128>(),
+        elapsed in 1..=u32::MAX,
+    ) {
+        // start close to the max value so that the accumulators wrap around
+        let start = U256::MAX - U256::from(start);
+
+        let (price_a_cumulative, price_b_cumulative) = crate::xyk::calculate_cumulative_prices(start, start, asset_a_reserve, asset_b_reserve, elapsed).unwrap();
+
+        let twap_a = rug::Rational::from(crate::xyk::calculate_twap(start, price_a_cumulative, elapsed).unwrap());
+        let twap_b = rug::Rational::from(crate::xyk::calculate_twap(start, price_b_cumulative, elapsed).unwrap());
+
+        let spot_a = rug::Rational::from((asset_b_reserve, asset_a_reserve));
+        let spot_b = rug::Rational::from((asset_a_reserve, asset_b_reserve));
+        // prices are truncated to 96 fractional bits and spot prices are greater than 2^-14
+        let tolerance = rug::Rational::from((1, 1u128 << 82));
+
+        assert!((twap_a - spot_a.clone()).abs() / spot_a <= tolerance);
+        assert!((twap_b - spot_b.clone()).abs() / spot_b <= tolerance);
+    }
+}
@@ -1,10 +1,14 @@
+use crate::fee::calculate_fee_amount_rounded_up;
+use crate::support::math::mul_div;
+use crate::support::rational::{round_to_rational, Rounding};
+use crate::types::Ratio;
 use crate::{
     ensure, round_up, to_balance, to_u256, MathError,
-    MathError::{InsufficientOutReserve, Overflow, ZeroReserve},
+    MathError::{InsufficientOutReserve, Overflow, ZeroDuration, ZeroReserve},
 };
 use core::convert::TryFrom;
 use num_traits::Zero;
-use primitive_types::U256;
+use primitive_types::{U256, U512};
 
 type Balance = u128;
 
@@ -183,3 +187,299 @@ pub fn calculate_shares(asset_reserve: Balance, asset_amount: Balance, share_iss
 
     to_balance!(result).ok()
 }
+
+/// Calculating amount to be received from the pool given the amount to be sent to the pool and both reserves, with fee applied.
+/// Fee is taken from the amount out and remains in the pool.
+/// Formula : AMOUNT_OUT = OUT_RESERVE * AMOUNT_IN / (IN_RESERVE + AMOUNT_IN) - FEE
+///
+/// - `in_reserve` - reserve amount of selling asset
+/// - `out_reserve` - reserve amount of buying asset
+/// - `amount_in` - amount
+/// - `fee` - trade fee as (numerator, denominator)
+///
+/// Returns tuple (amount out, fee amount) or MathError in case of error
+pub fn calculate_out_given_in_with_fee(
+    in_reserve: Balance,
+    out_reserve: Balance,
+    amount_in: Balance,
+    fee: (u32, u32),
+) -> Result<(Balance, Balance), MathError> {
+    let amount_out = calculate_out_given_in(in_reserve, out_reserve, amount_in)?;
+    let fee_amount = calculate_fee_amount_rounded_up(amount_out, fee)?;
+
+    let amount_out = amount_out.checked_sub(fee_amount).ok_or(Overflow)?;
+
+    Ok((amount_out, fee_amount))
+}
+
+/// Calculating amount to be sent to the pool given the amount to be received from the pool and both reserves, with fee applied.
+/// Fee is added on top of the amount in and remains in the pool.
+/// Formula : AMOUNT_IN = IN_RESERVE * AMOUNT_OUT / (OUT_RESERVE - AMOUNT_OUT) + 1 + FEE
+///
+/// - `out_reserve` - reserve amount of buying asset
+/// - `in_reserve` - reserve amount of selling asset
+/// - `amount_out` - buy amount
+/// - `fee` - trade fee as (numerator, denominator)
+///
+/// Returns tuple (amount in, fee amount) or MathError in case of error
+pub fn calculate_in_given_out_with_fee(
+    out_reserve: Balance,
+    in_reserve: Balance,
+    amount_out: Balance,
+    fee: (u32, u32),
+) -> Result<(Balance, Balance), MathError> {
+    let amount_in = calculate_in_given_out(out_reserve, in_reserve, amount_out)?;
+    let fee_amount = calculate_fee_amount_rounded_up(amount_in, fee)?;
+
+    let amount_in = amount_in.checked_add(fee_amount).ok_or(Overflow)?;
+
+    Ok((amount_in, fee_amount))
+}
+
+/// Calculating amount of asset a to swap for asset b when adding liquidity with asset a only,
+/// so that the remainder of asset a and the received asset b match the pool ratio after the swap.
+/// Fee is taken from the swapped amount out as in `calculate_out_given_in_with_fee`.
+///
+/// Derived from `(AMOUNT - S) / OUT = (A_RESERVE + S) / (B_RESERVE - OUT)` where `OUT = (1 - F) * S * B_RESERVE / (A_RESERVE + S)`:
+/// Formula : S = (sqrt(B^2 + 4 * AMOUNT * A_RESERVE * FEE_D^2) - B) / (2 * FEE_D)
+/// where B = A_RESERVE * (2 * FEE_D - FEE_N) - AMOUNT * FEE_N
+///
+/// - `asset_a_reserve` - reserve amount of asset a
+/// - `asset_b_reserve` - reserve amount of asset b
+/// - `amount` - amount of asset a provided
+/// - `fee` - trade fee as (numerator, denominator)
+///
+/// Returns MathError in case of error
+pub fn calculate_zap_swap_amount(
+    asset_a_reserve: Balance,
+    asset_b_reserve: Balance,
+    amount: Balance,
+    fee: (u32, u32),
+) -> Result<Balance, MathError> {
+    ensure!(asset_a_reserve != 0, ZeroReserve);
+    ensure!(asset_b_reserve != 0, ZeroReserve);
+
+    if amount.is_zero() {
+        return Ok(Balance::zero());
+    }
+
+    let (fee_n, fee_d) = if fee.0.is_zero() || fee.1.is_zero() {
+        (0, 1)
+    } else {
+        fee
+    };
+    ensure!(fee_n < fee_d, Overflow);
+
+    let (reserve_hp, amount_hp, fee_n_hp, fee_d_hp) = (
+        U512::from(asset_a_reserve),
+        U512::from(amount),
+        U512::from(fee_n),
+        U512::from(fee_d),
+    );
+
+    let b_pos = reserve_hp
+        .checked_mul(
+            fee_d_hp
+                .checked_mul(U512::from(2))
+                .ok_or(Overflow)?
+                .checked_sub(fee_n_hp)
+                .ok_or(Overflow)?,
+        )
+        .ok_or(Overflow)?;
+    let b_neg = amount_hp.checked_mul(fee_n_hp).ok_or(Overflow)?;
+
+    let b_abs = abs_diff(b_pos, b_neg);
+    let discriminant = b_abs
+        .checked_mul(b_abs)
+        .and_then(|v| {
+            v.checked_add(
+                U512::from(4)
+                    .checked_mul(amount_hp)?
+                    .checked_mul(reserve_hp)?
+                    .checked_mul(fee_d_hp.checked_mul(fee_d_hp)?)?,
+            )
+        })
+        .ok_or(Overflow)?;
+    let root = discriminant.integer_sqrt();
+
+    let numerator = if b_pos >= b_neg {
+        root.checked_sub(b_abs)
+    } else {
+        root.checked_add(b_abs)
+    }
+    .ok_or(Overflow)?;
+
+    let swap_amount = numerator
+        .checked_div(fee_d_hp.checked_mul(U512::from(2)).ok_or(Overflow)?)
+        .ok_or(Overflow)?;
+
+    let swap_amount = Balance::try_from(swap_amount).map_err(|_| Overflow)?;
+
+    Ok(swap_amount.min(amount))
+}
+
+/// Calculating single-sided liquidity add of asset a.
+/// Part of the amount is swapped for asset b and both remaining amounts are added as liquidity.
+///
+/// - `asset_a_reserve` - reserve amount of asset a
+/// - `asset_b_reserve` - reserve amount of asset b
+/// - `amount` - amount of asset a provided
+/// - `fee` - trade fee as (numerator, denominator)
+/// - `share_issuance` - total issuance of share asset
+///
+/// Returns tuple (swap amount, amount of asset a added, amount of asset b added, shares) or MathError in case of error
+pub fn calculate_zap_in(
+    asset_a_reserve: Balance,
+    asset_b_reserve: Balance,
+    amount: Balance,
+    fee: (u32, u32),
+    share_issuance: Balance,
+) -> Result<(Balance, Balance, Balance, Balance), MathError> {
+    let swap_amount = calculate_zap_swap_amount(asset_a_reserve, asset_b_reserve, amount, fee)?;
+
+    let (amount_b, _) = calculate_out_given_in_with_fee(asset_a_reserve, asset_b_reserve, swap_amount, fee)?;
+
+    let asset_a_reserve = asset_a_reserve.checked_add(swap_amount).ok_or(Overflow)?;
+    let asset_b_reserve = asset_b_reserve.checked_sub(amount_b).ok_or(Overflow)?;
+    ensure!(asset_b_reserve != 0, ZeroReserve);
+
+    let amount_a = amount.checked_sub(swap_amount).ok_or(Overflow)?;
+
+    // Shares are given for the smaller side so any dust left by rounding remains in the pool
+    let shares_a = calculate_shares(asset_a_reserve, amount_a, share_issuance).ok_or(Overflow)?;
+    let shares_b = calculate_shares(asset_b_reserve, amount_b, share_issuance).ok_or(Overflow)?;
+
+    Ok((swap_amount, amount_a, amount_b, shares_a.min(shares_b)))
+}
+
+/// Calculating single-sided liquidity removal to asset a.
+/// Liquidity is removed proportionally and the received asset b is swapped for asset a.
+///
+/// - `asset_a_reserve` - reserve amount of asset a
+/// - `asset_b_reserve` - reserve amount of asset b
+/// - `shares` - amount of shares to remove
+/// - `fee` - trade fee as (numerator, denominator)
+/// - `share_issuance` - total issuance of share asset
+///
+/// Returns tuple (amount of asset a received, amount of asset b swapped, fee amount) or MathError in case of error
+pub fn calculate_zap_out(
+    asset_a_reserve: Balance,
+    asset_b_reserve: Balance,
+    shares: Balance,
+    fee: (u32, u32),
+    share_issuance: Balance,
+) -> Result<(Balance, Balance, Balance), MathError> {
+    ensure!(shares <= share_issuance, InsufficientOutReserve);
+
+    let (removed_a, removed_b) = calculate_liquidity_out(asset_a_reserve, asset_b_reserve, shares, share_issuance)?;
+
+    let asset_a_reserve = asset_a_reserve.checked_sub(removed_a).ok_or(Overflow)?;
+    let asset_b_reserve = asset_b_reserve.checked_sub(removed_b).ok_or(Overflow)?;
+
+    let (swapped_a, fee_amount) = calculate_out_given_in_with_fee(asset_b_reserve, asset_a_reserve, removed_b, fee)?;
+
+    let amount_a = removed_a.checked_add(swapped_a).ok_or(Overflow)?;
+
+    Ok((amount_a, removed_b, fee_amount))
+}
+
+#[inline]
+fn abs_diff(a: U512, b: U512) -> U512 {
+    if a >= b {
+        a - b
+    } else {
+        b - a
+    }
+}
+
+/// Calculating price of asset a denominated in asset b as Q128.96 fixed point number.
+/// Formula : ASSET_B_RESERVE * 2^96 / ASSET_A_RESERVE
+///
+/// The result is less than `2^224` because both reserves fit into `u128`.
+///
+/// - `asset_a_reserve` - reserve amount of asset a
+/// - `asset_b_reserve` - reserve amount of asset b
+///
+/// Returns MathError in case of error
+pub fn calculate_price_q96(asset_a_reserve: Balance, asset_b_reserve: Balance) -> Result<U256, MathError> {
+    ensure!(asset_a_reserve != 0, ZeroReserve);
+
+    mul_div(
+        U256::from(asset_b_reserve),
+        U256::one() << 96,
+        U256::from(asset_a_reserve),
+        Rounding::Down,
+    )
+}
+
+/// Calculating new cumulative prices of both assets given the reserves during the elapsed period.
+/// Formula : PRICE_A_CUMULATIVE + ASSET_B_RESERVE * 2^96 / ASSET_A_RESERVE * ELAPSED
+/// Formula : PRICE_B_CUMULATIVE + ASSET_A_RESERVE * 2^96 / ASSET_B_RESERVE * ELAPSED
+///
+/// Accumulators are expected to overflow and are wrapped around. Only the difference between two
+/// snapshots is meaningful, see `calculate_twap`.
+///
+/// Prices are kept with 96 fractional bits only, so that a price accumulated over `u32::MAX` blocks
+/// is less than `2^256`. The difference of two snapshots is therefore exact for any reserves as long
+/// as the snapshots are at most `u32::MAX` blocks apart.
+///
+/// - `price_a_cumulative` - cumulative price of asset a denominated in asset b
+/// - `price_b_cumulative` - cumulative price of asset b denominated in asset a
+/// - `asset_a_reserve` - reserve amount of asset a during the elapsed period
+/// - `asset_b_reserve` - reserve amount of asset b during the elapsed period
+/// - `elapsed` - number of blocks (or time units) since last update
+///
+/// Returns MathError in case of error
+pub fn calculate_cumulative_prices(
+    price_a_cumulative: U256,
+    price_b_cumulative: U256,
+    asset_a_reserve: Balance,
+    asset_b_reserve: Balance,
+    elapsed: u32,
+) -> Result<(U256, U256), MathError> {
+    if elapsed.is_zero() {
+        return Ok((price_a_cumulative, price_b_cumulative));
+    }
+
+    let price_a = calculate_price_q96(asset_a_reserve, asset_b_reserve)?;
+    let price_b = calculate_price_q96(asset_b_reserve, asset_a_reserve)?;
+
+    // Bare math is safe because prices are less than 2^224
+    let elapsed = U256::from(elapsed);
+    let price_a_accumulated = price_a * elapsed;
+    let price_b_accumulated = price_b * elapsed;
+
+    Ok((
+        price_a_cumulative.overflowing_add(price_a_accumulated).0,
+        price_b_cumulative.overflowing_add(price_b_accumulated).0,
+    ))
+}
+
+/// Calculating time weighted average price between two snapshots of a cumulative price.
+/// Formula : (PRICE_CUMULATIVE_END - PRICE_CUMULATIVE_START) / ELAPSED / 2^96
+///
+/// The difference is calculated with wrap around, so it is correct for snapshots at most `u32::MAX`
+/// blocks apart, see `calculate_cumulative_prices`.
+///
+/// - `price_cumulative_start` - cumulative price at the start of the period
+/// - `price_cumulative_end` - cumulative price at the end of the period
+/// - `elapsed` - number of blocks (or time units) between the snapshots
+///
+/// Returns MathError in case of error
+pub fn calculate_twap(
+    price_cumulative_start: U256,
+    price_cumulative_end: U256,
+    elapsed: u32,
+) -> Result<Ratio, MathError> {
+    ensure!(elapsed != 0, ZeroDuration);
+
+    let price = price_cumulative_end
+        .overflowing_sub(price_cumulative_start)
+        .0
+        .checked_div(U256::from(elapsed))
+        .ok_or(Overflow)?;
+
+    let (n, d) = round_to_rational((price, U256::one() << 96), Rounding::Nearest);
+
+    Ok(Ratio::new(n, d))
+}
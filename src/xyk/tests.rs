@@ -1,7 +1,8 @@
 #![allow(unused_imports)]
-use crate::MathError::{InsufficientOutReserve, Overflow, ZeroReserve};
+use crate::MathError::{InsufficientOutReserve, InvalidFee, Overflow, ZeroDuration, ZeroReserve};
 
-use crate::types::Balance;
+use crate::types::{Balance, Ratio};
+use primitive_types::U256;
 use std::vec;
 
 #[test]
@@ -138,3 +139,260 @@ fn calculate_shares() {
         );
     }
 }
+
+#[test]
+fn out_given_in_with_fee_should_work() {
+    let one: Balance = 1_000_000_000_000;
+
+    let cases = vec![
+        (1000, 2000, 500, (2, 1000), Ok((664, 2)), "Easy case"),
+        (1000, 2000, 500, (0, 1000), Ok((666, 0)), "Zero fee"),
+        (1000, 2000, 500, (2, 0), Ok((666, 0)), "Zero fee denominator"),
+        (1000, 2000, 500, (1, 1), Ok((0, 666)), "Max fee"),
+        (1000, 2000, 500, (2, 1), Err(InvalidFee), "Fee above 100%"),
+        (1000, 1000, 0, (2, 1000), Ok((0, 0)), "Zero amount in"),
+        (
+            1000 * one,
+            1000 * one,
+            one,
+            (3, 1000),
+            Ok((996003996003, 2997002997)),
+            "Realistic case",
+        ),
+    ];
+
+    for case in cases {
+        assert_eq!(
+            crate::xyk::calculate_out_given_in_with_fee(case.0, case.1, case.2, case.3),
+            case.4,
+            "{}",
+            case.5
+        );
+    }
+}
+
+#[test]
+fn in_given_out_with_fee_should_work() {
+    let cases = vec![
+        (2000, 1000, 500, (2, 1000), Ok((335, 1)), "Easy case"),
+        (2000, 1000, 500, (0, 1000), Ok((334, 0)), "Zero fee"),
+        (2000, 1000, 500, (2, 0), Ok((334, 0)), "Zero fee denominator"),
+        (2000, 1000, 500, (1, 1), Ok((668, 334)), "Max fee"),
+        (2000, 1000, 500, (3, 2), Err(InvalidFee), "Fee above 100%"),
+        (1000, 1000, 0, (2, 1000), Ok((0, 0)), "Zero amount out"),
+        (
+            1000,
+            1000,
+            1000,
+            (2, 1000),
+            Err(ZeroReserve),
+            "Zero reserve after trade",
+        ),
+        (
+            u128::MAX,
+            u128::MAX,
+            u128::MAX / 2,
+            (2, 1000),
+            Err(Overflow),
+            "Overflow when fee added",
+        ),
+    ];
+
+    for case in cases {
+        assert_eq!(
+            crate::xyk::calculate_in_given_out_with_fee(case.0, case.1, case.2, case.3),
+            case.4,
+            "{}",
+            case.5
+        );
+    }
+}
+
+#[test]
+fn zap_in_should_work() {
+    let one: Balance = 1_000_000_000_000;
+
+    let cases = vec![
+        (1000, 1000, 100, (0, 1), 1000, Ok((48, 52, 45, 47)), "Easy case"),
+        (1000, 1000, 0, (2, 1000), 1000, Ok((0, 0, 0, 0)), "Zero amount"),
+        (0, 1000, 100, (2, 1000), 1000, Err(ZeroReserve), "Zero reserve a"),
+        (1000, 0, 100, (2, 1000), 1000, Err(ZeroReserve), "Zero reserve b"),
+        (1000, 1000, 100, (1, 1), 1000, Err(Overflow), "Max fee"),
+        (
+            1000 * one,
+            2000 * one,
+            100 * one,
+            (0, 1000),
+            1000 * one,
+            Ok((48808848170151, 51191151829849, 93074821508814, 48808848170150)),
+            "Zero fee",
+        ),
+        (
+            1000 * one,
+            2000 * one,
+            100 * one,
+            (3, 1000),
+            1000 * one,
+            Ok((48885753066137, 51114246933863, 92934994425203, 48731948912875)),
+            "Realistic case",
+        ),
+    ];
+
+    for case in cases {
+        assert_eq!(
+            crate::xyk::calculate_zap_in(case.0, case.1, case.2, case.3, case.4),
+            case.5,
+            "{}",
+            case.6
+        );
+    }
+}
+
+#[test]
+fn zap_out_should_work() {
+    let one: Balance = 1_000_000_000_000;
+
+    let cases = vec![
+        (1000, 1000, 100, (0, 1), 1000, Ok((190, 100, 0)), "Easy case"),
+        (1000, 1000, 0, (2, 1000), 1000, Ok((0, 0, 0)), "Zero shares"),
+        (
+            1000,
+            1000,
+            100,
+            (2, 1000),
+            0,
+            Err(InsufficientOutReserve),
+            "Zero issuance",
+        ),
+        (
+            1000,
+            1000,
+            1001,
+            (2, 1000),
+            1000,
+            Err(InsufficientOutReserve),
+            "Shares above issuance",
+        ),
+        (
+            1000 * one,
+            2000 * one,
+            10 * one,
+            (3, 1000),
+            1000 * one,
+            Ok((19870300000000, 20000000000000, 29700000000)),
+            "Realistic case",
+        ),
+    ];
+
+    for case in cases {
+        assert_eq!(
+            crate::xyk::calculate_zap_out(case.0, case.1, case.2, case.3, case.4),
+            case.5,
+            "{}",
+            case.6
+        );
+    }
+}
+
+#[test]
+fn cumulative_prices_should_work() {
+    let q96 = U256::one() << 96;
+
+    assert_eq!(crate::xyk::calculate_price_q96(1000, 2000), Ok(q96 * 2));
+    assert_eq!(crate::xyk::calculate_price_q96(2000, 1000), Ok(q96 / 2));
+    assert_eq!(
+        crate::xyk::calculate_price_q96(1, u128::MAX),
+        Ok(U256::from(u128::MAX) << 96)
+    );
+    assert_eq!(crate::xyk::calculate_price_q96(u128::MAX, 1), Ok(U256::zero()));
+    assert_eq!(crate::xyk::calculate_price_q96(0, 1000), Err(ZeroReserve));
+
+    let cases = vec![
+        (
+            (U256::zero(), U256::zero()),
+            (1000, 2000),
+            10,
+            Ok((q96 * 20, q96 * 5)),
+            "Easy case",
+        ),
+        (
+            (U256::MAX, U256::MAX),
+            (1000, 2000),
+            10,
+            Ok((q96 * 20 - 1, q96 * 5 - 1)),
+            "Accumulators wrap around",
+        ),
+        (
+            (U256::one(), U256::one()),
+            (1000, 2000),
+            0,
+            Ok((U256::one(), U256::one())),
+            "Zero elapsed",
+        ),
+        (
+            (U256::zero(), U256::zero()),
+            (1000, 0),
+            10,
+            Err(ZeroReserve),
+            "Zero reserve",
+        ),
+        (
+            (U256::zero(), U256::zero()),
+            (1, u128::MAX),
+            u32::MAX,
+            Ok(((U256::from(u128::MAX) << 96) * U256::from(u32::MAX), U256::zero())),
+            "Extreme price over max elapsed",
+        ),
+        (
+            (U256::MAX, U256::MAX),
+            (1, u128::MAX),
+            u32::MAX,
+            Ok(((U256::from(u128::MAX) << 96) * U256::from(u32::MAX) - 1, U256::MAX)),
+            "Extreme price wraps around",
+        ),
+    ];
+
+    for case in cases {
+        assert_eq!(
+            crate::xyk::calculate_cumulative_prices(case.0 .0, case.0 .1, case.1 .0, case.1 .1, case.2),
+            case.3,
+            "{}",
+            case.4
+        );
+    }
+}
+
+#[test]
+fn twap_should_work() {
+    let q96 = U256::one() << 96;
+
+    let cases = vec![
+        (
+            U256::zero(),
+            q96 * 20,
+            10,
+            Ok(Ratio::new(1 << 97, 1 << 96)),
+            "Easy case",
+        ),
+        (
+            U256::MAX,
+            q96 * 20 - 1,
+            10,
+            Ok(Ratio::new(1 << 97, 1 << 96)),
+            "Accumulator wrapped around",
+        ),
+        (q96, q96, 10, Ok(Ratio::new(0, 1 << 96)), "Zero price"),
+        (
+            U256::MAX,
+            (U256::from(u128::MAX) << 96) * U256::from(u32::MAX) - 1,
+            u32::MAX,
+            Ok(Ratio::new(u128::MAX, 1)),
+            "Extreme price over max elapsed",
+        ),
+        (U256::zero(), q96, 0, Err(ZeroDuration), "Zero elapsed"),
+    ];
+
+    for case in cases {
+        assert_eq!(crate::xyk::calculate_twap(case.0, case.1, case.2), case.3, "{}", case.4);
+    }
+}